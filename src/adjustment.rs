@@ -0,0 +1,11 @@
+/// A change of a team's points outside of games, e.g. a deduction for a
+/// financial breach. Kept by `Standings` so every adjustment can be audited.
+///
+/// Like `Game`, the team is a name unless `Standings` books it by `TeamId`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Adjustment<T = String> {
+    pub team: T,
+    pub points: i64,     // negative for deductions
+    pub reason: String,  // may be empty
+    pub matchday: usize, // the matchday from which on it counts
+}
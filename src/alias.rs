@@ -0,0 +1,231 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+use crate::ParseAliasesError;
+
+/// Maps the different spellings of a team to one canonical name.
+///
+/// Every name is normalised before it is looked up: it is brought into Unicode
+/// NFC and runs of whitespace are collapsed into single spaces. With
+/// `set_case_insensitive` the lookup ignores case as well. Names without an
+/// entry keep their (normalised) spelling, unless the table is a strict roster
+/// (`set_strict`), in which case they are unknown.
+///
+/// The text form lists one team per line, its canonical name optionally
+/// followed by `=` and the other spellings, separated by commas:
+///
+/// ```text
+/// # canonical name = other spellings
+/// San Jose Earthquakes = SJ Earthquakes, San José Earthquakes
+/// Aptos FC
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Aliases {
+    canonical: HashMap<String, String>, // normalised spelling -> canonical name
+    folded: HashMap<String, String>,    // the same, keyed by the lowercase spelling
+    case_insensitive: bool,
+    strict: bool, // names without an entry are unknown
+}
+
+impl Aliases {
+    pub fn new() -> Aliases {
+        Aliases::default()
+    }
+
+    /// Makes lookups ignore case, e.g. "APTOS FC" resolves to "Aptos FC".
+    pub fn set_case_insensitive(&mut self, case_insensitive: bool) {
+        self.case_insensitive = case_insensitive;
+    }
+
+    /// Turns the table into a roster: names not listed in it are unknown.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Registers `canonical` as a team, known under its own name.
+    pub fn add_team(&mut self, canonical: &str) -> Result<(), ParseAliasesError> {
+        self.add_alias(canonical, canonical)
+    }
+
+    /// Makes `alias` resolve to `canonical`. Fails if `alias` already stands
+    /// for another team.
+    pub fn add_alias(&mut self, canonical: &str, alias: &str) -> Result<(), ParseAliasesError> {
+        let canonical = normalize(canonical).into_owned();
+        let alias = normalize(alias).into_owned();
+        if canonical.is_empty() || alias.is_empty() {
+            return Err(ParseAliasesError(alias));
+        }
+        match self.canonical.get(&alias) {
+            Some(existing) if *existing != canonical => return Err(ParseAliasesError(alias)),
+            Some(_) => return Ok(()),
+            None => {}
+        }
+        // the first team wins if two spellings differ in case only
+        self.folded
+            .entry(alias.to_lowercase())
+            .or_insert_with(|| canonical.clone());
+        self.canonical.insert(alias, canonical);
+        Ok(())
+    }
+
+    /// Adds every team and spelling of `other` to this table, which becomes
+    /// case-insensitive or strict if `other` is. Fails on the first spelling
+    /// that stands for different teams in the two tables.
+    pub fn merge(&mut self, other: &Aliases) -> Result<(), ParseAliasesError> {
+        let mut spellings: Vec<(&String, &String)> = other.canonical.iter().collect();
+        // case-insensitive lookups depend on the order spellings are added in
+        spellings.sort_unstable();
+        for (alias, canonical) in spellings {
+            self.add_alias(canonical, alias)?;
+        }
+        self.case_insensitive |= other.case_insensitive;
+        self.strict |= other.strict;
+        Ok(())
+    }
+
+    /// The canonical names of all teams in the table, in no particular order.
+    pub fn teams(&self) -> impl Iterator<Item = &str> {
+        self.canonical
+            .iter()
+            .filter(|(spelling, canonical)| spelling == canonical)
+            .map(|(_, canonical)| canonical.as_str())
+    }
+
+    /// The canonical spelling of `name`, or `None` if the table is strict and
+    /// does not know it. Only allocates if the spelling changes.
+    pub fn resolve<'a>(&self, name: &'a str) -> Option<Cow<'a, str>> {
+        let name = normalize(name);
+        let canonical = if self.case_insensitive {
+            self.folded.get(&name.to_lowercase())
+        } else {
+            self.canonical.get(name.as_ref())
+        };
+        match canonical {
+            Some(canonical) if *canonical == name => Some(name),
+            Some(canonical) => Some(Cow::Owned(canonical.clone())),
+            None if self.strict => None,
+            None => Some(name),
+        }
+    }
+}
+
+impl FromStr for Aliases {
+    type Err = ParseAliasesError;
+
+    fn from_str(s: &str) -> Result<Aliases, ParseAliasesError> {
+        let mut aliases = Aliases::new();
+        let lines = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        for line in lines {
+            let (canonical, spellings) = match line.find('=') {
+                Some(eq) => (&line[..eq], Some(&line[eq + 1..])),
+                None => (line, None),
+            };
+            aliases.add_team(canonical)?;
+            for alias in spellings.into_iter().flat_map(|s| s.split(',')) {
+                aliases.add_alias(canonical, alias)?;
+            }
+        }
+        Ok(aliases)
+    }
+}
+
+/// `name` in Unicode NFC with runs of whitespace collapsed into single spaces.
+/// Only allocates if the name is not normalised already.
+fn normalize(name: &str) -> Cow<'_, str> {
+    let tidy = name
+        .split(' ')
+        .all(|word| !word.is_empty() && !word.contains(char::is_whitespace));
+    if tidy && is_nfc(name) {
+        return Cow::Borrowed(name);
+    }
+    let mut normalized = String::with_capacity(name.len());
+    for word in name.split_whitespace() {
+        if !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        normalized.extend(word.nfc());
+    }
+    Cow::Owned(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = "# comment
+San Jose Earthquakes = SJ Earthquakes, San Jos\u{e9} Earthquakes
+Aptos FC
+";
+
+    #[test]
+    fn normalize_collapses_whitespace_and_composes() {
+        assert!(matches!(normalize("Aptos FC"), Cow::Borrowed("Aptos FC")));
+        assert_eq!(normalize(" Aptos \t FC "), "Aptos FC");
+        assert_eq!(normalize("San Jose\u{301}"), "San Jos\u{e9}");
+    }
+
+    #[test]
+    fn resolve_maps_spellings_to_canonical_name() {
+        let mut aliases: Aliases = TABLE.parse().unwrap();
+        assert_eq!(
+            aliases.resolve("SJ  Earthquakes").as_deref(),
+            Some("San Jose Earthquakes")
+        );
+        assert_eq!(
+            aliases.resolve("San Jose\u{301} Earthquakes").as_deref(),
+            Some("San Jose Earthquakes")
+        );
+        assert!(matches!(
+            aliases.resolve("Aptos FC"),
+            Some(Cow::Borrowed("Aptos FC"))
+        ));
+        assert_eq!(aliases.resolve("APTOS FC").as_deref(), Some("APTOS FC"));
+        assert_eq!(aliases.resolve("Felton").as_deref(), Some("Felton"));
+
+        aliases.set_case_insensitive(true);
+        aliases.set_strict(true);
+        assert_eq!(aliases.resolve("APTOS FC").as_deref(), Some("Aptos FC"));
+        assert_eq!(aliases.resolve("Felton"), None);
+    }
+
+    #[test]
+    fn merge_keeps_the_spellings_of_both_tables() {
+        let mut aliases: Aliases = TABLE.parse().unwrap();
+        let mut roster: Aliases = "Aptos FC = Aptos\nFelton Lumberjacks".parse().unwrap();
+        roster.set_strict(true);
+        aliases.merge(&roster).unwrap();
+        assert!(aliases.is_strict());
+        assert_eq!(aliases.resolve("Aptos").as_deref(), Some("Aptos FC"));
+        assert_eq!(
+            aliases.resolve("SJ Earthquakes").as_deref(),
+            Some("San Jose Earthquakes")
+        );
+        assert_eq!(aliases.teams().count(), 3);
+        let conflicting: Aliases = "Aptos United = Aptos".parse().unwrap();
+        assert_eq!(
+            aliases.merge(&conflicting),
+            Err(ParseAliasesError("Aptos".to_string()))
+        );
+    }
+
+    #[test]
+    fn conflicting_aliases_are_rejected() {
+        assert_eq!(
+            "Aptos FC = Aptos\nAptos United = Aptos"
+                .parse::<Aliases>()
+                .err(),
+            Some(ParseAliasesError("Aptos".to_string()))
+        );
+        assert!("Aptos FC = ".parse::<Aliases>().is_err());
+    }
+}
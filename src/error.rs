@@ -0,0 +1,268 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Reasons a line of input could not be turned into a `Game` or a `Line`.
+///
+/// Every variant carries the 1-based line number and the 1-based byte column
+/// at which the problem was detected, so callers can point at the exact spot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseGameError {
+    MissingSeparator { line: usize, column: usize },
+    MissingTeamName { line: usize, column: usize },
+    InvalidScore { line: usize, column: usize },
+    ScoreOutOfRange { line: usize, column: usize },
+    TrailingGarbage { line: usize, column: usize },
+    InvalidMarker { line: usize, column: usize },
+    InvalidAnnotation { line: usize, column: usize },
+    InvalidCommand { line: usize, column: usize },
+    UnknownTeam { line: usize, column: usize },
+    InvalidDeclaration { line: usize, column: usize },
+}
+
+impl ParseGameError {
+    pub fn line(&self) -> usize {
+        match *self {
+            ParseGameError::MissingSeparator { line, .. }
+            | ParseGameError::MissingTeamName { line, .. }
+            | ParseGameError::InvalidScore { line, .. }
+            | ParseGameError::ScoreOutOfRange { line, .. }
+            | ParseGameError::TrailingGarbage { line, .. }
+            | ParseGameError::InvalidMarker { line, .. }
+            | ParseGameError::InvalidAnnotation { line, .. }
+            | ParseGameError::InvalidCommand { line, .. }
+            | ParseGameError::UnknownTeam { line, .. }
+            | ParseGameError::InvalidDeclaration { line, .. } => line,
+        }
+    }
+
+    pub fn column(&self) -> usize {
+        match *self {
+            ParseGameError::MissingSeparator { column, .. }
+            | ParseGameError::MissingTeamName { column, .. }
+            | ParseGameError::InvalidScore { column, .. }
+            | ParseGameError::ScoreOutOfRange { column, .. }
+            | ParseGameError::TrailingGarbage { column, .. }
+            | ParseGameError::InvalidMarker { column, .. }
+            | ParseGameError::InvalidAnnotation { column, .. }
+            | ParseGameError::InvalidCommand { column, .. }
+            | ParseGameError::UnknownTeam { column, .. }
+            | ParseGameError::InvalidDeclaration { column, .. } => column,
+        }
+    }
+
+    // short description without the position, used by `Display`
+    fn reason(&self) -> &'static str {
+        match self {
+            ParseGameError::MissingSeparator { .. } => "missing \", \" between home and away team",
+            ParseGameError::MissingTeamName { .. } => "missing team name",
+            ParseGameError::InvalidScore { .. } => "invalid score",
+            ParseGameError::ScoreOutOfRange { .. } => "score out of range",
+            ParseGameError::TrailingGarbage { .. } => "unexpected input after score",
+            ParseGameError::InvalidMarker { .. } => "invalid matchday marker",
+            ParseGameError::InvalidAnnotation { .. } => "invalid annotation",
+            ParseGameError::InvalidCommand { .. } => "invalid command",
+            ParseGameError::UnknownTeam { .. } => "team not on the roster",
+            ParseGameError::InvalidDeclaration { .. } => "invalid or conflicting team declaration",
+        }
+    }
+}
+
+impl fmt::Display for ParseGameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line(),
+            self.column(),
+            self.reason()
+        )
+    }
+}
+
+impl Error for ParseGameError {}
+
+/// Why a line of input was skipped: it is malformed, or it is well-formed
+/// but `Standings` refused to book it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineError {
+    Parse(ParseGameError),
+    Rejected { line: usize, error: BookingError },
+}
+
+impl LineError {
+    pub fn line(&self) -> usize {
+        match self {
+            LineError::Parse(err) => err.line(),
+            LineError::Rejected { line, .. } => *line,
+        }
+    }
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LineError::Parse(err) => err.fmt(f),
+            LineError::Rejected { line, error } => write!(f, "line {}: {}", line, error),
+        }
+    }
+}
+
+impl Error for LineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LineError::Parse(err) => Some(err),
+            LineError::Rejected { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<ParseGameError> for LineError {
+    fn from(err: ParseGameError) -> Self {
+        LineError::Parse(err)
+    }
+}
+
+/// Errors that abort an ingestion run.
+#[derive(Debug)]
+pub enum IngestError {
+    Io(io::Error),
+    Parse(ParseGameError),
+    Rejected { line: usize, error: BookingError }, // a well-formed line `Standings` refused
+    Overflow(OverflowError),
+}
+
+impl fmt::Display for IngestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IngestError::Io(err) => write!(f, "cannot read input: {}", err),
+            IngestError::Parse(err) => err.fmt(f),
+            IngestError::Rejected { line, error } => write!(f, "line {}: {}", line, error),
+            IngestError::Overflow(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for IngestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IngestError::Io(err) => Some(err),
+            IngestError::Parse(err) => Some(err),
+            IngestError::Rejected { error, .. } => Some(error),
+            IngestError::Overflow(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for IngestError {
+    fn from(err: io::Error) -> Self {
+        IngestError::Io(err)
+    }
+}
+
+impl From<ParseGameError> for IngestError {
+    fn from(err: ParseGameError) -> Self {
+        IngestError::Parse(err)
+    }
+}
+
+impl From<OverflowError> for IngestError {
+    fn from(err: OverflowError) -> Self {
+        IngestError::Overflow(err)
+    }
+}
+
+/// A team's goals or points no longer fit into its `TeamRecord`.
+/// The game or adjustment that caused it has not been booked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverflowError {
+    pub team: String,
+}
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "goals or points of {} out of range", self.team)
+    }
+}
+
+impl Error for OverflowError {}
+
+/// Reasons `Standings` refused to book a game or command. Nothing has been
+/// changed when one of them is returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookingError {
+    Overflow(OverflowError),
+    UnknownTeam(String), // the league has a roster and it does not list the team
+    MatchdayOutOfOrder(usize), // a matchday started after a later one had games
+    FutureMatchday(usize), // a game credited to a matchday that has not started
+}
+
+impl fmt::Display for BookingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BookingError::Overflow(err) => err.fmt(f),
+            BookingError::UnknownTeam(team) => write!(f, "{} is not on the roster", team),
+            BookingError::MatchdayOutOfOrder(matchday) => {
+                write!(f, "matchday {} cannot start after a later one", matchday)
+            }
+            BookingError::FutureMatchday(matchday) => {
+                write!(f, "matchday {} has not started yet", matchday)
+            }
+        }
+    }
+}
+
+impl Error for BookingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BookingError::Overflow(err) => Some(err),
+            BookingError::UnknownTeam(_)
+            | BookingError::MatchdayOutOfOrder(_)
+            | BookingError::FutureMatchday(_) => None,
+        }
+    }
+}
+
+impl From<OverflowError> for BookingError {
+    fn from(err: OverflowError) -> Self {
+        BookingError::Overflow(err)
+    }
+}
+
+/// A tiebreaker name that is not known, see `Tiebreaker`'s `FromStr` implementation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTiebreakerError(pub String);
+
+impl fmt::Display for ParseTiebreakerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown tiebreaker {:?}", self.0)
+    }
+}
+
+impl Error for ParseTiebreakerError {}
+
+/// A scoring system that could not be understood, see `ScoringRules`'
+/// `FromStr` implementation. Holds the offending item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseScoringError(pub String);
+
+impl fmt::Display for ParseScoringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid scoring rule {:?}", self.0)
+    }
+}
+
+impl Error for ParseScoringError {}
+
+/// A team alias table that could not be understood, see `Aliases`' `FromStr`
+/// implementation. Holds the offending spelling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAliasesError(pub String);
+
+impl fmt::Display for ParseAliasesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid or conflicting alias {:?}", self.0)
+    }
+}
+
+impl Error for ParseAliasesError {}
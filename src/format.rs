@@ -0,0 +1,639 @@
+use crate::{MatchdaySnapshot, Note, RankedEntry, Stats, Streak, StreakKind};
+use std::io::{self, Write};
+
+/// How `Printer` shows a team's position in the rankings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionStyle {
+    Hidden,     // "Aptos FC, 6 pts"
+    Numbered,   // "1. Aptos FC, 6 pts"
+    MarkShared, // "1= Aptos FC, 6 pts" when the position is shared, "1. " otherwise
+}
+
+/// Writes matchday snapshots to any `io::Write` sink.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Printer {
+    pub top: usize,         // number of teams in `write_rankings`
+    pub include_ties: bool, // extends the top-n by teams sharing the n-th position
+    pub position_style: PositionStyle,
+    pub show_movement: bool, // "▲2", "▼1", "=" or "new" since the previous matchday
+    pub show_form: bool,     // the results in `RankedEntry::form`, e.g. "WWDLW"
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Printer {
+            top: 3,
+            include_ties: false,
+            position_style: PositionStyle::Hidden,
+            show_movement: false,
+            show_form: false,
+        }
+    }
+}
+
+impl Printer {
+    /// The top-n teams with their points, one per line after a "Matchday n" header.
+    /// Nothing is written before any team has played.
+    ///
+    /// Awarded, annulled and expunged results of the listed teams are pointed
+    /// out below. Revised snapshots are headed "Matchday n (revised)" and
+    /// followed by the games that were played late. With `show_movement`,
+    /// teams that were outside the top-n at the previous matchday are marked
+    /// "new".
+    pub fn write_rankings<W: Write>(
+        &self,
+        w: &mut W,
+        snapshot: &MatchdaySnapshot,
+    ) -> io::Result<()> {
+        if snapshot.entries.is_empty() {
+            return Ok(());
+        }
+        write_header(w, snapshot)?;
+        let entries = snapshot.top(self.top, self.include_ties);
+        for entry in entries {
+            writeln!(
+                w,
+                "{}{}, {} pt{}{}",
+                self.label(entry),
+                entry.team,
+                entry.record.points,
+                pluralize(entry.record.points),
+                self.indicator(entry, Some(self.top))
+            )?;
+        }
+        write_notes(w, entries)?;
+        write_late_games(w, snapshot)
+    }
+
+    /// The complete league table with all columns.
+    pub fn write_table<W: Write>(&self, w: &mut W, snapshot: &MatchdaySnapshot) -> io::Result<()> {
+        if snapshot.entries.is_empty() {
+            return Ok(());
+        }
+        write_header(w, snapshot)?;
+        self.write_rows(w, snapshot, self.show_movement, self.show_form)
+    }
+
+    /// A table from `Standings::form_table`, headed "Form (last n games)" and
+    /// always with the form column. It has no movement column, as there is no
+    /// earlier form table to compare with.
+    pub fn write_form_table<W: Write>(
+        &self,
+        w: &mut W,
+        snapshot: &MatchdaySnapshot,
+        n: usize,
+    ) -> io::Result<()> {
+        if snapshot.entries.is_empty() {
+            return Ok(());
+        }
+        writeln!(w, "Form (last {} game{})", n, pluralize(n as i64))?;
+        self.write_rows(w, snapshot, false, true)
+    }
+
+    // the column headers and a row per team, followed by the notes
+    fn write_rows<W: Write>(
+        &self,
+        w: &mut W,
+        snapshot: &MatchdaySnapshot,
+        show_movement: bool,
+        show_form: bool,
+    ) -> io::Result<()> {
+        let width = snapshot
+            .entries
+            .iter()
+            .map(|entry| entry.team.chars().count())
+            .chain(Some("Team".len()))
+            .max()
+            .unwrap_or(0);
+        write!(
+            w,
+            "{:>3} {:<width$} {:>3} {:>3} {:>3} {:>3} {:>4} {:>4} {:>4} {:>4}",
+            "Pos",
+            "Team",
+            "P",
+            "W",
+            "D",
+            "L",
+            "GF",
+            "GA",
+            "GD",
+            "Pts",
+            width = width
+        )?;
+        if show_movement {
+            write!(w, " {:>4}", "+/-")?;
+        }
+        if show_form {
+            write!(w, " Form")?;
+        }
+        writeln!(w)?;
+        for entry in &snapshot.entries {
+            let pos = match self.position_style {
+                PositionStyle::MarkShared if entry.shared => format!("{}=", entry.position),
+                _ => entry.position.to_string(),
+            };
+            let r = &entry.record;
+            write!(
+                w,
+                "{:>3} {:<width$} {:>3} {:>3} {:>3} {:>3} {:>4} {:>4} {:>4} {:>4}",
+                pos,
+                entry.team,
+                r.played,
+                r.won,
+                r.drawn,
+                r.lost,
+                r.goals_for,
+                r.goals_against,
+                signed(r.goal_difference()),
+                r.points,
+                width = width
+            )?;
+            if show_movement {
+                let movement = entry.movement.map(|m| m.to_string()).unwrap_or_default();
+                write!(w, " {:>4}", movement)?;
+            }
+            if show_form {
+                write!(w, " {}", form(entry))?;
+            }
+            writeln!(w)?;
+        }
+        write_notes(w, &snapshot.entries)?;
+        write_late_games(w, snapshot)
+    }
+
+    /// The complete league table as CSV, one line per team in ranking order.
+    /// With `show_movement`, a column holds the position at the previous
+    /// matchday, empty for teams that had none, and with `show_form` a last
+    /// one the form, e.g. "WWDLW".
+    pub fn write_csv<W: Write>(&self, w: &mut W, snapshot: &MatchdaySnapshot) -> io::Result<()> {
+        write!(
+            w,
+            "matchday,position,team,played,won,drawn,lost,goals_for,goals_against,goal_difference,points"
+        )?;
+        if self.show_movement {
+            write!(w, ",previous_position")?;
+        }
+        if self.show_form {
+            write!(w, ",form")?;
+        }
+        writeln!(w)?;
+        for entry in &snapshot.entries {
+            let r = &entry.record;
+            write!(
+                w,
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                snapshot.matchday,
+                entry.position,
+                csv_field(&entry.team),
+                r.played,
+                r.won,
+                r.drawn,
+                r.lost,
+                r.goals_for,
+                r.goals_against,
+                r.goal_difference(),
+                r.points
+            )?;
+            if self.show_movement {
+                let previous = entry
+                    .movement
+                    .and_then(|movement| movement.previous_position(entry.position));
+                match previous {
+                    Some(previous) => write!(w, ",{}", previous)?,
+                    None => write!(w, ",")?,
+                }
+            }
+            if self.show_form {
+                write!(w, ",{}", form(entry))?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    /// Every team's position and points after each matchday in `history`,
+    /// one row per team in the order of the last matchday, e.g. "1 (9)".
+    /// A "-" marks matchdays before the team had a place in the table.
+    pub fn write_timeline<W: Write>(
+        &self,
+        w: &mut W,
+        history: &[MatchdaySnapshot],
+    ) -> io::Result<()> {
+        let mut teams: Vec<&str> = Vec::new();
+        for snapshot in history.iter().rev() {
+            for entry in &snapshot.entries {
+                if !teams.contains(&entry.team.as_str()) {
+                    teams.push(&entry.team);
+                }
+            }
+        }
+        if teams.is_empty() {
+            return Ok(());
+        }
+        let cells: Vec<Vec<String>> = teams
+            .iter()
+            .map(|team| {
+                history
+                    .iter()
+                    .map(|snapshot| match snapshot.entry(team) {
+                        Some(entry) => format!("{} ({})", entry.position, entry.record.points),
+                        None => "-".to_string(),
+                    })
+                    .collect()
+            })
+            .collect();
+        let headers: Vec<String> = history
+            .iter()
+            .map(|snapshot| format!("MD{}", snapshot.matchday))
+            .collect();
+        let width = teams
+            .iter()
+            .map(|team| team.chars().count())
+            .max()
+            .unwrap_or(0);
+        let widths: Vec<usize> = headers
+            .iter()
+            .enumerate()
+            .map(|(idx, header)| {
+                cells
+                    .iter()
+                    .map(|row| row[idx].len())
+                    .chain(Some(header.len()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        writeln!(w, "Timeline")?;
+        write!(w, "{:<width$}", "Team", width = width)?;
+        for (header, cell_width) in headers.iter().zip(&widths) {
+            write!(w, " {:>width$}", header, width = cell_width)?;
+        }
+        writeln!(w)?;
+        for (team, row) in teams.iter().zip(&cells) {
+            write!(w, "{:<width$}", team, width = width)?;
+            for (cell, cell_width) in row.iter().zip(&widths) {
+                write!(w, " {:>width$}", cell, width = cell_width)?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    /// The records in `stats`, one per line after a "Records" header, e.g.
+    /// "Longest winning streak: 3 games, Aptos FC (matchdays 2-4, ongoing)".
+    /// Shared records are separated by semicolons.
+    pub fn write_stats<W: Write>(&self, w: &mut W, stats: &Stats) -> io::Result<()> {
+        match &stats.team {
+            Some(team) => writeln!(w, "Records: {}", team)?,
+            None => writeln!(w, "Records")?,
+        }
+        for &kind in &StreakKind::ALL {
+            let label = match kind {
+                StreakKind::Winning => "Longest winning streak",
+                StreakKind::Unbeaten => "Longest unbeaten run",
+                StreakKind::CleanSheets => "Most consecutive clean sheets",
+            };
+            let streaks: Vec<String> = stats
+                .longest(kind)
+                .map(|streak| describe(streak, stats.team.is_none()))
+                .collect();
+            match stats.longest(kind).next() {
+                Some(streak) => writeln!(
+                    w,
+                    "{}: {} game{}, {}",
+                    label,
+                    streak.length,
+                    pluralize(streak.length as i64),
+                    streaks.join("; ")
+                )?,
+                None => writeln!(w, "{}: -", label)?,
+            }
+        }
+        for (label, games) in &[
+            ("Biggest win", &stats.biggest_wins),
+            ("Highest-scoring game", &stats.highest_scoring),
+        ] {
+            let games: Vec<String> = games.iter().map(ToString::to_string).collect();
+            if games.is_empty() {
+                writeln!(w, "{}: -", label)?;
+            } else {
+                writeln!(w, "{}: {}", label, games.join("; "))?;
+            }
+        }
+        Ok(())
+    }
+
+    // " ▲2" and the like if `show_movement` is set, " new" for teams that
+    // were not among the `top` teams before
+    fn indicator(&self, entry: &RankedEntry, top: Option<usize>) -> String {
+        let movement = match entry.movement {
+            Some(movement) if self.show_movement => movement,
+            _ => return String::new(),
+        };
+        let previous = movement.previous_position(entry.position);
+        match (previous, top) {
+            (Some(previous), Some(top)) if previous > top => " new".to_string(),
+            _ => format!(" {}", movement),
+        }
+    }
+
+    fn label(&self, entry: &RankedEntry) -> String {
+        match self.position_style {
+            PositionStyle::Hidden => String::new(),
+            PositionStyle::MarkShared if entry.shared => format!("{}= ", entry.position),
+            _ => format!("{}. ", entry.position),
+        }
+    }
+}
+
+fn write_header<W: Write>(w: &mut W, snapshot: &MatchdaySnapshot) -> io::Result<()> {
+    if snapshot.is_revised() {
+        writeln!(w, "Matchday {} (revised)", snapshot.matchday)
+    } else {
+        writeln!(w, "Matchday {}", snapshot.matchday)
+    }
+}
+
+fn write_notes<W: Write>(w: &mut W, entries: &[RankedEntry]) -> io::Result<()> {
+    for entry in entries {
+        for note in &entry.notes {
+            match note {
+                Note::Awarded => writeln!(w, "* {}: awarded result", entry.team)?,
+                Note::Annulled => writeln!(w, "* {}: game annulled", entry.team)?,
+                Note::Expunged(withdrawn) => writeln!(
+                    w,
+                    "* {}: results against {} expunged",
+                    entry.team, withdrawn
+                )?,
+                Note::Adjusted { points, reason } if reason.is_empty() => writeln!(
+                    w,
+                    "* {}: {} pt{} adjusted",
+                    entry.team,
+                    signed(*points),
+                    pluralize(*points)
+                )?,
+                Note::Adjusted { points, reason } => writeln!(
+                    w,
+                    "* {}: {} pt{} ({})",
+                    entry.team,
+                    signed(*points),
+                    pluralize(*points),
+                    reason
+                )?,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_late_games<W: Write>(w: &mut W, snapshot: &MatchdaySnapshot) -> io::Result<()> {
+    for game in &snapshot.late_games {
+        writeln!(w, "* played late: {}", game)?;
+    }
+    Ok(())
+}
+
+fn pluralize<'a>(n: i64) -> &'a str {
+    match n {
+        1 | -1 => "",
+        _ => "s",
+    }
+}
+
+// "Aptos FC (matchdays 2-4, ongoing)", without the team for a team's own records
+fn describe(streak: &Streak, with_team: bool) -> String {
+    let matchdays = if streak.first == streak.last {
+        format!("matchday {}", streak.first)
+    } else {
+        format!("matchdays {}-{}", streak.first, streak.last)
+    };
+    let ongoing = if streak.ongoing { ", ongoing" } else { "" };
+    if with_team {
+        format!("{} ({}{})", streak.team, matchdays, ongoing)
+    } else {
+        format!("{}{}", matchdays, ongoing)
+    }
+}
+
+// "WWDLW", the most recent result last
+fn form(entry: &RankedEntry) -> String {
+    entry.form.iter().map(ToString::to_string).collect()
+}
+
+// goal difference as printed in tables, "+3", "0", "-2"
+fn signed(n: i64) -> String {
+    if n > 0 {
+        format!("+{}", n)
+    } else {
+        n.to_string()
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains(&[',', '"'][..]) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{sample_standings, sample_standings_after};
+    use crate::Standings;
+
+    fn sample_snapshot() -> MatchdaySnapshot {
+        sample_standings_after(9).snapshot()
+    }
+
+    fn rankings(printer: &Printer) -> String {
+        let mut out = Vec::new();
+        printer
+            .write_rankings(&mut out, &sample_snapshot())
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn write_rankings_matches_expected_output() {
+        assert_eq!(
+            rankings(&Printer::default()),
+            "Matchday 3\nAptos FC, 6 pts\nFelton Lumberjacks, 6 pts\nMonterey United, 6 pts\n"
+        );
+    }
+
+    #[test]
+    fn write_rankings_with_positions_and_ties() {
+        let printer = Printer {
+            top: 1,
+            include_ties: true,
+            position_style: PositionStyle::MarkShared,
+            ..Default::default()
+        };
+        assert_eq!(
+            rankings(&printer),
+            "Matchday 3\n1= Aptos FC, 6 pts\n1= Felton Lumberjacks, 6 pts\n1= Monterey United, 6 pts\n"
+        );
+        let printer = Printer {
+            top: 4,
+            include_ties: false,
+            position_style: PositionStyle::Numbered,
+            ..Default::default()
+        };
+        assert!(rankings(&printer)
+            .ends_with("\n1. Monterey United, 6 pts\n4. Capitola Seahorses, 4 pts\n"));
+    }
+
+    #[test]
+    fn write_csv_exports_table_in_order() {
+        let mut out = Vec::new();
+        Printer::default()
+            .write_csv(&mut out, &sample_snapshot())
+            .unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[1], "3,1,Aptos FC,3,2,0,1,5,4,1,6");
+        assert_eq!(lines[6], "3,6,San Jose Earthquakes,3,0,1,2,6,11,-5,1");
+    }
+
+    #[test]
+    fn write_table_fits_the_header_to_short_names() {
+        let mut standings = Standings::default();
+        standings.ingest("A 1, B 0".parse().unwrap()).unwrap();
+        let mut out = Vec::new();
+        Printer::default()
+            .write_table(&mut out, &standings.snapshot())
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[1], "Pos Team   P   W   D   L   GF   GA   GD  Pts");
+        assert_eq!(lines[2], "  1 A      1   1   0   0    1    0   +1    3");
+    }
+
+    #[test]
+    fn write_rankings_marks_revised_snapshots() {
+        let mut snapshot = sample_snapshot();
+        snapshot.late_games = vec!["Aptos FC 2, Monterey United 0 (matchday 3)"
+            .parse()
+            .unwrap()];
+        let mut out = Vec::new();
+        Printer::default()
+            .write_rankings(&mut out, &snapshot)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("Matchday 3 (revised)\n"));
+        assert!(out.ends_with("\n* played late: Aptos FC 2, Monterey United 0 (matchday 3)\n"));
+    }
+
+    #[test]
+    fn write_rankings_adds_footnotes_for_listed_teams() {
+        let mut snapshot = sample_snapshot();
+        snapshot.entries[0].notes = vec![Note::Expunged("Felton Lumberjacks".to_string())];
+        snapshot.entries[5].notes = vec![Note::Awarded];
+        let mut out = Vec::new();
+        Printer::default()
+            .write_rankings(&mut out, &snapshot)
+            .unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .ends_with("pts\n* Aptos FC: results against Felton Lumberjacks expunged\n"));
+    }
+
+    #[test]
+    fn write_timeline_lists_positions_per_matchday() {
+        let mut standings = sample_standings_after(6);
+        standings.finish();
+        let mut out = Vec::new();
+        Printer::default()
+            .write_timeline(&mut out, standings.history())
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "Timeline");
+        assert_eq!(lines[1], "Team                   MD1   MD2");
+        assert_eq!(lines[2], "Capitola Seahorses   1 (3) 1 (4)");
+        assert_eq!(lines.len(), 8);
+    }
+
+    #[test]
+    fn write_rankings_shows_movement() {
+        let standings = sample_standings_after(9);
+        let printer = Printer {
+            position_style: PositionStyle::Numbered,
+            show_movement: true,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        printer
+            .write_rankings(&mut out, &standings.snapshot())
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Matchday 3\n1. Aptos FC, 6 pts \u{25b2}1\n1. Felton Lumberjacks, 6 pts \u{25b2}1\n1. Monterey United, 6 pts \u{25b2}1\n"
+        );
+        let mut out = Vec::new();
+        let top = Printer {
+            top: 1,
+            ..printer.clone()
+        };
+        top.write_rankings(&mut out, &standings.snapshot()).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .contains("1. Aptos FC, 6 pts new\n"));
+        let mut out = Vec::new();
+        printer.write_csv(&mut out, &standings.snapshot()).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        assert!(csv
+            .lines()
+            .next()
+            .unwrap()
+            .ends_with(",points,previous_position"));
+        assert!(csv.lines().nth(4).unwrap().ends_with(",1"));
+    }
+
+    #[test]
+    fn write_form_table_ranks_recent_games() {
+        let standings = sample_standings();
+        let mut out = Vec::new();
+        let printer = Printer {
+            show_movement: true,
+            ..Default::default()
+        };
+        printer
+            .write_form_table(&mut out, &standings.form_table(2).unwrap(), 2)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "Form (last 2 games)");
+        assert!(lines[1].ends_with(" Pts Form"));
+        assert!(lines[2].starts_with("  1 Aptos FC ") && lines[2].ends_with("    6 WW"));
+    }
+
+    #[test]
+    fn write_stats_lists_league_records() {
+        let standings = sample_standings();
+        let mut out = Vec::new();
+        Printer::default()
+            .write_stats(&mut out, &standings.stats())
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Records
+Longest winning streak: 3 games, Aptos FC (matchdays 2-4, ongoing)
+Longest unbeaten run: 3 games, Aptos FC (matchdays 2-4, ongoing)
+Most consecutive clean sheets: 2 games, Capitola Seahorses (matchdays 1-2)
+Biggest win: San Jose Earthquakes 1, Felton Lumberjacks 4
+Highest-scoring game: Capitola Seahorses 5, San Jose Earthquakes 5
+"
+        );
+        let mut out = Vec::new();
+        Printer::default()
+            .write_stats(&mut out, &standings.team_stats("Santa Cruz Slugs").unwrap())
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("Records: Santa Cruz Slugs\nLongest winning streak: -\n"));
+        assert!(out.contains("\nMost consecutive clean sheets: 1 game, matchday 2\n"));
+    }
+}
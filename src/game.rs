@@ -0,0 +1,673 @@
+use crate::{ParseGameError, TeamId};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome<T> {
+    WINLOSS((T, T)),  // tuple of winner, loser, decided in regulation time
+    OVERTIME((T, T)), // decided in overtime or extra time
+    SHOOTOUT((T, T)), // decided by a shootout or penalties
+    DRAW((T, T)),
+}
+
+/// How a game ended, see `Game::decision`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Regulation,
+    Overtime,                           // "OT" or "(a.e.t.)"
+    Shootout,                           // "SO", the score includes the deciding goal
+    Penalties { home: u32, away: u32 }, // "(4-3 pens)", the score is the one before penalties
+}
+
+// Refactor-NOTE
+// Team names are interned into `TeamId`s once a game is booked, see `Teams`.
+// Scores could also be made up of more detailed data, such as vectors of tuples of (playername, minute scored).
+
+/// A single game. Teams are given by name as parsed from the input, or by
+/// `TeamId` once the game has been booked by `Standings`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Game<T = String> {
+    pub(crate) home: T,
+    pub(crate) home_score: u32,
+    pub(crate) away: T,
+    pub(crate) away_score: u32,
+    pub(crate) matchday: Option<usize>, // original matchday of a postponed or rescheduled game
+    pub(crate) tries: Option<(u32, u32)>, // home and away tries, for rugby bonus points
+    pub(crate) decision: Decision,
+    pub(crate) awarded: bool, // result awarded by the league, e.g. a 3-0 forfeit
+}
+
+/// A game borrowing its team names from the parsed input, see `Game::parse_ref`.
+pub type GameRef<'a> = Game<&'a str>;
+
+impl Game {
+    /// Parses a single line of input, reporting errors against `line` (1-based).
+    /// See `Game::parse_ref` for the format.
+    pub fn parse_line(raw: &str, line: usize) -> Result<Game, ParseGameError> {
+        Game::parse_ref(raw, line).map(GameRef::into_owned)
+    }
+
+    pub fn outcome(&self) -> Outcome<&str> {
+        self.outcome_by(|team| team.as_str())
+    }
+}
+
+impl<'a> Game<&'a str> {
+    /// Parses a single line of input without allocating, the team names are
+    /// borrowed from `raw`. Errors are reported against `line` (1-based).
+    ///
+    /// The away score may be followed by annotations in parentheses:
+    /// `(matchday 3)` credits a postponed or rescheduled game to its original matchday,
+    /// `(tries 4-1)` records the home and away tries of a rugby game,
+    /// `(a.e.t.)` and `(4-3 pens)` mark games decided in extra time or by penalties,
+    /// `(awarded)` marks a result awarded by the league, e.g. after a forfeit.
+    /// A bare `OT` or `SO` after the away score marks a game decided in overtime
+    /// or by a shootout, with the deciding goal included in the score, so it
+    /// cannot be level.
+    pub fn parse_ref(raw: &'a str, line: usize) -> Result<GameRef<'a>, ParseGameError> {
+        // NOTE: assuming "{home name} {home score}, {away name} {away score}" format.
+        // If the input format cannot be guaranteed, this will be the place to adjust.
+        let raw = raw.trim_end();
+        let sep = match raw.find(", ") {
+            Some(sep) => sep,
+            None => {
+                return Err(ParseGameError::MissingSeparator {
+                    line,
+                    column: raw.len() + 1,
+                })
+            }
+        };
+        let away_offset = sep + 2;
+        if let Some(extra) = raw[away_offset..].find(", ") {
+            return Err(ParseGameError::TrailingGarbage {
+                line,
+                column: away_offset + extra + 1,
+            });
+        }
+        let away = &raw[away_offset..];
+        // annotations follow the away score, the team name may contain parentheses too
+        let annotations = away
+            .match_indices(" (")
+            .map(|(pos, _)| pos)
+            .chain(Some(away.len()))
+            .filter(|&pos| {
+                let (core, _) = split_decision(&away[..pos]);
+                core.rsplit(' ').next().is_some_and(is_digits)
+            })
+            .last()
+            .or_else(|| away.find(" ("))
+            .unwrap_or(away.len());
+        let (core, decision) = split_decision(&away[..annotations]);
+        let (home, home_score) = parse_side(&raw[..sep], 0, line)?;
+        let (away, away_score) = parse_side(core, away_offset, line)?;
+        // the score includes the deciding goal, so it cannot be level
+        if decision != Decision::Regulation && home_score == away_score {
+            return Err(ParseGameError::InvalidAnnotation {
+                line,
+                column: away_offset + core.len() + 2,
+            });
+        }
+        let mut game = Game {
+            home,
+            home_score,
+            away,
+            away_score,
+            matchday: None,
+            tries: None,
+            decision,
+            awarded: false,
+        };
+        game.parse_annotations(
+            &raw[away_offset + annotations..],
+            away_offset + annotations,
+            line,
+        )?;
+        Ok(game)
+    }
+
+    pub fn outcome(&self) -> Outcome<&'a str> {
+        self.outcome_by(|team| *team)
+    }
+
+    /// The same game owning its team names.
+    pub fn into_owned(self) -> Game {
+        self.map_teams(str::to_string)
+    }
+}
+
+impl Game<TeamId> {
+    pub fn outcome(&self) -> Outcome<TeamId> {
+        self.outcome_by(|team| *team)
+    }
+}
+
+impl<T> Game<T> {
+    /// The same game with each team converted by `f`, e.g. interned into a `TeamId`.
+    pub fn map_teams<U, F: FnMut(T) -> U>(self, mut f: F) -> Game<U> {
+        Game {
+            home: f(self.home),
+            home_score: self.home_score,
+            away: f(self.away),
+            away_score: self.away_score,
+            matchday: self.matchday,
+            tries: self.tries,
+            decision: self.decision,
+            awarded: self.awarded,
+        }
+    }
+
+    /// The same game borrowing its teams.
+    pub(crate) fn as_ref(&self) -> Game<&T> {
+        Game {
+            home: &self.home,
+            home_score: self.home_score,
+            away: &self.away,
+            away_score: self.away_score,
+            matchday: self.matchday,
+            tries: self.tries,
+            decision: self.decision,
+            awarded: self.awarded,
+        }
+    }
+
+    /// The matchday the game was originally scheduled for, if it was tagged with one.
+    pub fn matchday(&self) -> Option<usize> {
+        self.matchday
+    }
+
+    /// Credits the game to its original `matchday`, for games played out of order.
+    pub fn with_matchday(mut self, matchday: usize) -> Game<T> {
+        self.matchday = Some(matchday);
+        self
+    }
+
+    /// The home and away tries, if the result carries them.
+    pub fn tries(&self) -> Option<(u32, u32)> {
+        self.tries
+    }
+
+    /// Records the home and away tries of a rugby game.
+    pub fn with_tries(mut self, home: u32, away: u32) -> Game<T> {
+        self.tries = Some((home, away));
+        self
+    }
+
+    /// How the game was decided.
+    pub fn decision(&self) -> Decision {
+        self.decision
+    }
+
+    /// Marks the game as decided in overtime, by a shootout or by penalties.
+    pub fn with_decision(mut self, decision: Decision) -> Game<T> {
+        self.decision = decision;
+        self
+    }
+
+    /// Whether the result was awarded rather than played, e.g. after a forfeit.
+    pub fn is_awarded(&self) -> bool {
+        self.awarded
+    }
+
+    /// Marks the result as awarded. The loser counts as having forfeited.
+    pub fn awarded(mut self) -> Game<T> {
+        self.awarded = true;
+        self
+    }
+
+    // Applies "(...)" annotations following the away score.
+    // `offset` is the byte position of `s` within the whole line, for error columns.
+    fn parse_annotations(
+        &mut self,
+        s: &str,
+        offset: usize,
+        line: usize,
+    ) -> Result<(), ParseGameError> {
+        let mut pos = 0;
+        while pos < s.len() {
+            let rest = &s[pos..];
+            let start = pos + rest.len() - rest.trim_start().len();
+            if start == s.len() {
+                break;
+            }
+            let column = offset + start + 1;
+            if !s[start..].starts_with('(') {
+                return Err(ParseGameError::TrailingGarbage { line, column });
+            }
+            let end = match s[start..].find(')') {
+                Some(end) => start + end,
+                None => return Err(ParseGameError::InvalidAnnotation { line, column }),
+            };
+            if !self.apply_annotation(&s[start + 1..end]) {
+                return Err(ParseGameError::InvalidAnnotation { line, column });
+            }
+            pos = end + 1;
+        }
+        Ok(())
+    }
+
+    // Returns false for annotations that are not understood.
+    fn apply_annotation(&mut self, annotation: &str) -> bool {
+        let mut words = annotation.split_whitespace();
+        let words = (words.next(), words.next(), words.next());
+        let is =
+            |word: &str, keywords: &[&str]| keywords.iter().any(|k| word.eq_ignore_ascii_case(k));
+        match words {
+            (Some(keyword), Some(number), None) if is(keyword, &["matchday", "md", "round"]) => {
+                match number.parse() {
+                    Ok(matchday) => {
+                        self.matchday = Some(matchday);
+                        true
+                    }
+                    Err(_) => false,
+                }
+            }
+            (Some(keyword), None, None) if is(keyword, &["awarded", "forfeit"]) => {
+                self.awarded = true;
+                true
+            }
+            (Some(keyword), None, None) if is(keyword, &["a.e.t.", "aet"]) => {
+                if self.decision == Decision::Regulation {
+                    self.decision = Decision::Overtime;
+                }
+                true
+            }
+            (Some(shootout), Some(keyword), None) if is(keyword, &["pens", "pen.", "p"]) => {
+                match parse_pair(shootout) {
+                    // penalties only follow a draw and cannot end level
+                    Some((home, away)) if self.home_score == self.away_score && home != away => {
+                        self.decision = Decision::Penalties { home, away };
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            (Some(keyword), Some(tries), None) if is(keyword, &["tries"]) => {
+                match parse_pair(tries) {
+                    Some(tries) => {
+                        self.tries = Some(tries);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn outcome_by<'a, U, F: Fn(&'a T) -> U>(&'a self, team: F) -> Outcome<U> {
+        let teams = match self.home_result() {
+            Ordering::Greater => (team(&self.home), team(&self.away)),
+            Ordering::Less => (team(&self.away), team(&self.home)),
+            Ordering::Equal => return Outcome::DRAW((team(&self.home), team(&self.away))),
+        };
+        match self.decision {
+            Decision::Regulation => Outcome::WINLOSS(teams),
+            Decision::Overtime => Outcome::OVERTIME(teams),
+            Decision::Shootout | Decision::Penalties { .. } => Outcome::SHOOTOUT(teams),
+        }
+    }
+
+    // whether the home team won, drew or lost, including penalties
+    pub(crate) fn home_result(&self) -> Ordering {
+        match self.decision {
+            Decision::Penalties { home, away } => home.cmp(&away),
+            _ => self.home_score.cmp(&self.away_score),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Game<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {}, {} {}",
+            self.home, self.home_score, self.away, self.away_score
+        )?;
+        match self.decision {
+            Decision::Regulation => {}
+            Decision::Overtime => write!(f, " OT")?,
+            Decision::Shootout => write!(f, " SO")?,
+            Decision::Penalties { .. } => {}
+        }
+        if let Some(matchday) = self.matchday {
+            write!(f, " (matchday {})", matchday)?;
+        }
+        if let Some((home, away)) = self.tries {
+            write!(f, " (tries {}-{})", home, away)?;
+        }
+        if let Decision::Penalties { home, away } = self.decision {
+            write!(f, " ({}-{} pens)", home, away)?;
+        }
+        if self.awarded {
+            write!(f, " (awarded)")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Game {
+    type Err = ParseGameError;
+
+    fn from_str(raw: &str) -> Result<Game, ParseGameError> {
+        Game::parse_line(raw, 1)
+    }
+}
+
+// Splits one side of a game ("{name} {score}") into its name and score.
+// `offset` is the byte position of `side` within the whole line, for error columns.
+fn parse_side(side: &str, offset: usize, line: usize) -> Result<(&str, u32), ParseGameError> {
+    let column = |pos: usize| offset + pos + 1;
+    // the score is the last all-digit token; anything after it is not part of the game
+    let (mut last, mut score) = (None, None);
+    for token in tokens(side) {
+        if is_digits(token.1) {
+            score = Some(token);
+        }
+        last = Some(token);
+    }
+    let last = match last {
+        Some(last) => last,
+        None => {
+            return Err(ParseGameError::MissingTeamName {
+                line,
+                column: column(0),
+            })
+        }
+    };
+    let score = match score {
+        Some(score) => score,
+        None => {
+            return Err(ParseGameError::InvalidScore {
+                line,
+                column: column(last.0),
+            })
+        }
+    };
+    if score.0 != last.0 {
+        let trailing = tokens(side).find(|(pos, _)| *pos > score.0).unwrap();
+        return Err(ParseGameError::TrailingGarbage {
+            line,
+            column: column(trailing.0),
+        });
+    }
+    let name = side[..score.0].trim();
+    if name.is_empty() {
+        return Err(ParseGameError::MissingTeamName {
+            line,
+            column: column(0),
+        });
+    }
+    let value = score
+        .1
+        .parse()
+        .map_err(|_| ParseGameError::ScoreOutOfRange {
+            line,
+            column: column(score.0),
+        })?;
+    Ok((name, value))
+}
+
+// whitespace separated tokens together with their byte offset
+fn tokens(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    s.split(' ')
+        .scan(0, |pos, t| {
+            let start = *pos;
+            *pos += t.len() + 1;
+            Some((start, t))
+        })
+        .filter(|(_, t)| !t.is_empty())
+}
+
+// strips a trailing "OT" or "SO" marker off the away side
+fn split_decision(side: &str) -> (&str, Decision) {
+    if let Some(space) = side.rfind(' ') {
+        let marker = &side[space + 1..];
+        let decision = if marker.eq_ignore_ascii_case("OT") {
+            Decision::Overtime
+        } else if marker.eq_ignore_ascii_case("SO") {
+            Decision::Shootout
+        } else {
+            return (side, Decision::Regulation);
+        };
+        return (&side[..space], decision);
+    }
+    (side, Decision::Regulation)
+}
+
+// "4-1"
+fn parse_pair(s: &str) -> Option<(u32, u32)> {
+    let dash = s.find('-')?;
+    Some((s[..dash].parse().ok()?, s[dash + 1..].parse().ok()?))
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_from_str_works() {
+        let line = "San Jose Earthquakes 3, Santa Cruz Slugs 3";
+        let game = Game::from_str(line).unwrap();
+        assert_eq!(game.home, "San Jose Earthquakes");
+        assert_eq!(game.away, "Santa Cruz Slugs");
+        assert_eq!(game.home_score, 3);
+        assert_eq!(game.away_score, 3);
+    }
+
+    #[test]
+    fn game_parse_line_reports_position() {
+        assert_eq!(
+            Game::parse_line("Aptos FC 1 Monterey United 0", 7).err(),
+            Some(ParseGameError::MissingSeparator {
+                line: 7,
+                column: 29
+            })
+        );
+        assert_eq!(
+            Game::parse_line("3, Aptos FC 0", 2).err(),
+            Some(ParseGameError::MissingTeamName { line: 2, column: 1 })
+        );
+        assert_eq!(
+            Game::parse_line("Aptos FC x, Monterey United 0", 3).err(),
+            Some(ParseGameError::InvalidScore {
+                line: 3,
+                column: 10
+            })
+        );
+        assert_eq!(
+            Game::parse_line("Aptos FC 1, Monterey United 4294967296", 4).err(),
+            Some(ParseGameError::ScoreOutOfRange {
+                line: 4,
+                column: 29
+            })
+        );
+        assert_eq!(
+            Game::parse_line("Aptos FC 1, Monterey United 0 extra", 5).err(),
+            Some(ParseGameError::TrailingGarbage {
+                line: 5,
+                column: 31
+            })
+        );
+        assert_eq!(
+            Game::parse_line("Aptos FC 1, Monterey United 0, Felton Lumberjacks 2", 6).err(),
+            Some(ParseGameError::TrailingGarbage {
+                line: 6,
+                column: 30
+            })
+        );
+    }
+
+    #[test]
+    fn game_from_str_accepts_high_scores() {
+        let game: Game = "Golden State Warriors 132, Boston Celtics 118"
+            .parse()
+            .unwrap();
+        assert_eq!(game.home_score, 132);
+        assert_eq!(game.away_score, 118);
+    }
+
+    #[test]
+    fn game_from_str_tolerates_line_endings() {
+        let game: Game = "Capitola Seahorses 1, Aptos FC 0\r\n".parse().unwrap();
+        assert_eq!(game.away, "Aptos FC");
+        assert_eq!(game.away_score, 0);
+    }
+
+    #[test]
+    fn outcome_draw_works() {
+        let line = "San Jose Earthquakes 3, Santa Cruz Slugs 3";
+        let game = Game::from_str(line).unwrap();
+        assert_eq!(
+            game.outcome(),
+            Outcome::DRAW(("San Jose Earthquakes", "Santa Cruz Slugs"))
+        );
+    }
+
+    #[test]
+    fn outcome_home_win_works() {
+        let line = "Capitola Seahorses 1, Aptos FC 0";
+        let game = Game::from_str(line).unwrap();
+        assert_eq!(
+            game.outcome(),
+            Outcome::WINLOSS(("Capitola Seahorses", "Aptos FC"))
+        );
+    }
+
+    #[test]
+    fn outcome_away_win_works() {
+        let line = "San Jose Earthquakes 1, Felton Lumberjacks 4";
+        let game = Game::from_str(line).unwrap();
+        assert_eq!(
+            game.outcome(),
+            Outcome::WINLOSS(("Felton Lumberjacks", "San Jose Earthquakes"))
+        );
+    }
+
+    #[test]
+    fn game_parses_matchday_annotation() {
+        let game: Game = "Aptos FC 2, Monterey United 0 (matchday 3)"
+            .parse()
+            .unwrap();
+        assert_eq!(game.away, "Monterey United");
+        assert_eq!(game.away_score, 0);
+        assert_eq!(game.matchday(), Some(3));
+        assert_eq!(
+            game.to_string(),
+            "Aptos FC 2, Monterey United 0 (matchday 3)"
+        );
+        let game: Game = "Aptos FC 2, Monterey United 0  (MD 4)".parse().unwrap();
+        assert_eq!(game.matchday(), Some(4));
+        assert_eq!(
+            Game::parse_line("Aptos FC 2, Monterey United 0 (matchday three)", 2).err(),
+            Some(ParseGameError::InvalidAnnotation {
+                line: 2,
+                column: 31
+            })
+        );
+        assert_eq!(
+            Game::parse_line("Aptos FC 2, Monterey United 0 (matchday 3) late", 2).err(),
+            Some(ParseGameError::TrailingGarbage {
+                line: 2,
+                column: 44
+            })
+        );
+    }
+
+    #[test]
+    fn game_allows_parentheses_in_away_team_names() {
+        let game: Game = "Aptos FC 1, Real Madrid (B) 2".parse().unwrap();
+        assert_eq!(game.away, "Real Madrid (B)");
+        assert_eq!(game.away_score, 2);
+        let game: Game = "Aptos FC 1, Real Madrid (B) 2 OT (md 3)".parse().unwrap();
+        assert_eq!(game.away, "Real Madrid (B)");
+        assert_eq!(game.decision, Decision::Overtime);
+        assert_eq!(game.matchday(), Some(3));
+    }
+
+    #[test]
+    fn game_parses_tries_annotation() {
+        let game: Game = "Saracens 24, Leicester Tigers 19 (tries 4-1) (round 2)"
+            .parse()
+            .unwrap();
+        assert_eq!(game.tries(), Some((4, 1)));
+        assert_eq!(game.matchday(), Some(2));
+        assert_eq!(
+            game.to_string(),
+            "Saracens 24, Leicester Tigers 19 (matchday 2) (tries 4-1)"
+        );
+        assert_eq!(
+            Game::parse_line("Saracens 24, Leicester Tigers 19 (tries 4)", 1).err(),
+            Some(ParseGameError::InvalidAnnotation {
+                line: 1,
+                column: 34
+            })
+        );
+    }
+
+    #[test]
+    fn game_parses_overtime_and_shootouts() {
+        let game: Game = "Sharks 3, Kings 2 OT".parse().unwrap();
+        assert_eq!(game.decision(), Decision::Overtime);
+        assert_eq!(game.outcome(), Outcome::OVERTIME(("Sharks", "Kings")));
+        assert_eq!(game.to_string(), "Sharks 3, Kings 2 OT");
+
+        let game: Game = "Sharks 2, Kings 3 so (round 4)".parse().unwrap();
+        assert_eq!(game.outcome(), Outcome::SHOOTOUT(("Kings", "Sharks")));
+        assert_eq!(game.to_string(), "Sharks 2, Kings 3 SO (matchday 4)");
+
+        let game: Game = "Aptos FC 2, Monterey United 2 (a.e.t.)".parse().unwrap();
+        assert_eq!(game.decision(), Decision::Overtime);
+        assert_eq!(
+            game.outcome(),
+            Outcome::DRAW(("Aptos FC", "Monterey United"))
+        );
+
+        let game: Game = "Aptos FC 1, Monterey United 1 (a.e.t.) (3-4 pens)"
+            .parse()
+            .unwrap();
+        assert_eq!(game.decision(), Decision::Penalties { home: 3, away: 4 });
+        assert_eq!(
+            game.outcome(),
+            Outcome::SHOOTOUT(("Monterey United", "Aptos FC"))
+        );
+        assert_eq!(game.home_result(), Ordering::Less);
+        assert_eq!(game.to_string(), "Aptos FC 1, Monterey United 1 (3-4 pens)");
+
+        assert_eq!(
+            Game::parse_line("Sharks 2, Kings 2 SO", 1).err(),
+            Some(ParseGameError::InvalidAnnotation {
+                line: 1,
+                column: 19
+            })
+        );
+        assert_eq!(
+            Game::parse_line("Aptos FC 2, Monterey United 1 (4-3 pens)", 1).err(),
+            Some(ParseGameError::InvalidAnnotation {
+                line: 1,
+                column: 31
+            })
+        );
+    }
+
+    #[test]
+    fn parse_ref_borrows_team_names() {
+        let raw = String::from("Aptos FC 2, Monterey United 0 (md 3)");
+        let game = GameRef::parse_ref(&raw, 1).unwrap();
+        assert_eq!(game.home, "Aptos FC");
+        assert!(std::ptr::eq(game.home, &raw[..8]));
+        assert_eq!(
+            game.outcome(),
+            Outcome::WINLOSS(("Aptos FC", "Monterey United"))
+        );
+        assert_eq!(game.clone().into_owned(), raw.parse::<Game>().unwrap());
+        assert_eq!(
+            game.to_string(),
+            "Aptos FC 2, Monterey United 0 (matchday 3)"
+        );
+    }
+}
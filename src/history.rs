@@ -0,0 +1,101 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::GameRef;
+
+/// A game in the log kept by `Standings`, see `Standings::log`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedGame<'a> {
+    pub game: GameRef<'a>,
+    pub position: usize, // number of games ingested before this one
+    pub matchday: usize, // the matchday the game is credited to
+    pub played: usize,   // the matchday during which it was ingested
+}
+
+/// A game as seen by one of its teams, see `Standings::fixtures`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fixture<'a> {
+    pub team: &'a str,
+    pub game: GameRef<'a>,
+    pub position: usize, // number of games ingested before this one
+    pub matchday: usize, // the matchday the game is credited to
+    pub points: i64,     // earned by `team`
+}
+
+impl<'a> Fixture<'a> {
+    pub fn is_home(&self) -> bool {
+        self.game.home == self.team
+    }
+
+    pub fn opponent(&self) -> &'a str {
+        if self.is_home() {
+            self.game.away
+        } else {
+            self.game.home
+        }
+    }
+
+    pub fn scored(&self) -> u32 {
+        if self.is_home() {
+            self.game.home_score
+        } else {
+            self.game.away_score
+        }
+    }
+
+    pub fn conceded(&self) -> u32 {
+        if self.is_home() {
+            self.game.away_score
+        } else {
+            self.game.home_score
+        }
+    }
+
+    /// The result for `team`, a shootout or penalty win counts as a win.
+    pub fn result(&self) -> MatchResult {
+        if self.is_home() {
+            self.game.home_result().into()
+        } else {
+            self.game.home_result().reverse().into()
+        }
+    }
+}
+
+/// The result of a game for one of its teams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchResult {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// From the point of view of the team on the left-hand side of the comparison.
+impl From<Ordering> for MatchResult {
+    fn from(result: Ordering) -> MatchResult {
+        match result {
+            Ordering::Greater => MatchResult::Win,
+            Ordering::Equal => MatchResult::Draw,
+            Ordering::Less => MatchResult::Loss,
+        }
+    }
+}
+
+/// "W", "D" or "L", as in a form guide.
+impl fmt::Display for MatchResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            MatchResult::Win => "W",
+            MatchResult::Draw => "D",
+            MatchResult::Loss => "L",
+        })
+    }
+}
+
+/// A team's place in the table after a closed matchday, see `Standings::timeline`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimelineEntry {
+    pub matchday: usize,
+    pub position: usize,
+    pub shared: bool, // whether another team held the same position
+    pub points: i64,
+}
@@ -0,0 +1,478 @@
+use crate::input::column_of;
+use crate::{BookingError, IngestError, Line, LineError, LineRef, ParseGameError, Standings};
+use std::borrow::Cow;
+use std::fmt;
+use std::io::BufRead;
+
+/// How the ingestion driver reacts to a line that is not a valid game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Strict,  // stop at the first bad line
+    Lenient, // skip bad lines and keep a diagnostic for each of them
+}
+
+/// A line that was skipped during a lenient run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub raw: String,
+    pub error: LineError,
+}
+
+impl Diagnostic {
+    pub fn line(&self) -> usize {
+        self.error.line()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({:?})", self.error, self.raw)
+    }
+}
+
+/// Summary of an ingestion run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub ingested: usize, // number of games handed to `Standings::ingest`
+    pub diagnostics: Vec<Diagnostic>,
+    pub idle_teams: Vec<String>, // declared teams without a game, see `Standings::idle_teams`
+}
+
+impl Report {
+    pub fn skipped(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ingested {} game{}, skipped {} line{}",
+            self.ingested,
+            if self.ingested == 1 { "" } else { "s" },
+            self.skipped(),
+            if self.skipped() == 1 { "" } else { "s" },
+        )?;
+        for diagnostic in &self.diagnostics {
+            write!(f, "\n  {}", diagnostic)?;
+        }
+        for team in &self.idle_teams {
+            write!(f, "\n  {} has not played", team)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads games line by line from `reader` and feeds them into `standings`.
+/// The last matchday is closed with `Standings::finish` at the end of the input,
+/// so observers of `standings` are notified about every matchday.
+///
+/// Matchday markers are passed on to `Standings::start_matchday`, commands to
+/// `Standings::annul`, `Standings::withdraw` and `Standings::adjust`, blank lines
+/// and comments are ignored. In `Mode::Strict` the first malformed line, or
+/// line `standings` refuses to book, aborts the run; in `Mode::Lenient` it is
+/// recorded in the returned `Report` instead.
+///
+/// A `# Teams` block at the top of the input declares the league's teams with
+/// `Standings::declare_team`; the report lists those that never played.
+/// Team names are mapped to their canonical spelling with the `Aliases` of
+/// `standings`, names missing from a strict roster count as malformed lines.
+///
+/// Lines are read into a single reused buffer and parsed without copying, so
+/// only the team names `standings` has not seen before are allocated.
+pub fn ingest<R: BufRead>(
+    mut reader: R,
+    standings: &mut Standings,
+    mode: Mode,
+) -> Result<Report, IngestError> {
+    let mut session = Session::new(standings, mode);
+    let mut buf = String::new();
+    for n in 1.. {
+        buf.clear();
+        if reader.read_line(&mut buf)? == 0 {
+            break;
+        }
+        let line = buf.strip_suffix('\n').unwrap_or(&buf);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        session.line(line, n)?;
+    }
+    Ok(session.finish())
+}
+
+/// Like `ingest`, but for input that is already in memory, e.g. a
+/// memory-mapped archive. Team names are borrowed straight from `input`.
+pub fn ingest_str(
+    input: &str,
+    standings: &mut Standings,
+    mode: Mode,
+) -> Result<Report, IngestError> {
+    let mut session = Session::new(standings, mode);
+    for (n, line) in input.lines().enumerate() {
+        session.line(line, n + 1)?;
+    }
+    Ok(session.finish())
+}
+
+// state carried from one line of input to the next
+struct Session<'s> {
+    standings: &'s mut Standings,
+    mode: Mode,
+    report: Report,
+    declaring: bool, // inside the `# Teams` block
+}
+
+impl<'s> Session<'s> {
+    fn new(standings: &'s mut Standings, mode: Mode) -> Session<'s> {
+        Session {
+            standings,
+            mode,
+            report: Report::default(),
+            declaring: false,
+        }
+    }
+
+    fn line(&mut self, raw: &str, n: usize) -> Result<(), IngestError> {
+        let parsed = if self.declaring {
+            LineRef::parse_declaration(raw, n)
+        } else {
+            LineRef::parse_ref(raw, n)
+        };
+        let aliases = self.standings.aliases();
+        let result = parsed
+            .and_then(|line| Ok((line.clone().resolve(aliases, raw, n)?, line)))
+            .map_err(IngestError::from)
+            .and_then(|(line, parsed)| self.book(line, &parsed, raw, n));
+        let error = match result {
+            Err(IngestError::Parse(err)) if self.mode == Mode::Lenient => LineError::Parse(err),
+            Err(IngestError::Rejected { line, error }) if self.mode == Mode::Lenient => {
+                LineError::Rejected { line, error }
+            }
+            result => return result,
+        };
+        self.report.diagnostics.push(Diagnostic {
+            raw: raw.to_string(),
+            error,
+        });
+        Ok(())
+    }
+
+    // `parsed` is `line` before its team names were resolved
+    fn book(
+        &mut self,
+        line: Line<Cow<str>>,
+        parsed: &LineRef,
+        raw: &str,
+        n: usize,
+    ) -> Result<(), IngestError> {
+        let column = raw.len() - raw.trim_start().len() + 1;
+        let standings = &mut *self.standings;
+        match line {
+            Line::Teams | Line::Team { .. } | Line::Comment => {}
+            _ => self.declaring = false,
+        }
+        match line {
+            Line::Game(game) => {
+                standings
+                    .ingest(game)
+                    .map_err(|err| rejected(err, standings, parsed, raw, n))?;
+                self.report.ingested += 1;
+            }
+            Line::Matchday(matchday) => {
+                standings
+                    .start_matchday(matchday)
+                    .map_err(|err| rejected(err, standings, parsed, raw, n))?;
+            }
+            Line::Annul { home, away } => {
+                standings.annul(&home, &away)?;
+            }
+            Line::Withdraw(team) => standings
+                .withdraw(&team)
+                .map_err(|err| rejected(err, standings, parsed, raw, n))?,
+            Line::Adjust {
+                team,
+                points,
+                reason,
+            } => standings
+                .adjust(&team, points, &reason)
+                .map_err(|err| rejected(err, standings, parsed, raw, n))?,
+            // a header is only recognised before the first game
+            Line::Teams => self.declaring = self.report.ingested == 0,
+            Line::Team { name, aliases } => standings
+                .declare_team(&name, aliases.iter().map(AsRef::as_ref))
+                .map_err(|_| ParseGameError::InvalidDeclaration { line: n, column })?,
+            Line::Comment | Line::Blank => {}
+        }
+        Ok(())
+    }
+
+    // closes the last matchday and warns about declared teams that never played
+    fn finish(mut self) -> Report {
+        self.standings.finish();
+        self.report.idle_teams = self
+            .standings
+            .idle_teams()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        self.report
+    }
+}
+
+// what `err` means for line `n`, `parsed` being the line as it was read:
+// only overflows abort a lenient run, other errors skip the line
+fn rejected(
+    err: BookingError,
+    standings: &Standings,
+    parsed: &LineRef,
+    raw: &str,
+    n: usize,
+) -> IngestError {
+    let column = raw.len() - raw.trim_start().len() + 1;
+    match err {
+        BookingError::Overflow(err) => IngestError::Overflow(err),
+        BookingError::UnknownTeam(team) => {
+            // the spelling in `raw` that stands for `team`
+            let aliases = standings.aliases();
+            let spelling = parsed
+                .teams()
+                .find(|name| aliases.resolve(name).as_deref() == Some(team.as_str()));
+            ParseGameError::UnknownTeam {
+                line: n,
+                column: spelling.map_or(column, |name| column_of(raw, name)),
+            }
+            .into()
+        }
+        err @ BookingError::MatchdayOutOfOrder(_) | err @ BookingError::FutureMatchday(_) => {
+            IngestError::Rejected {
+                line: n,
+                error: err,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::SAMPLE_INPUT;
+    use crate::{Aliases, Event};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    const INPUT: &str = "San Jose Earthquakes 3, Santa Cruz Slugs 3
+Capitola Seahorses one, Aptos FC 0
+
+Felton Lumberjacks 2, Monterey United 0
+Felton Lumberjacks 1 Aptos FC 2
+";
+
+    #[test]
+    fn lenient_mode_collects_diagnostics() {
+        let mut standings = Standings::default();
+        let report = ingest(INPUT.as_bytes(), &mut standings, Mode::Lenient).unwrap();
+        assert_eq!(report.ingested, 2);
+        assert_eq!(report.skipped(), 2);
+        assert_eq!(report.diagnostics[0].line(), 2);
+        assert_eq!(
+            report.diagnostics[0].raw,
+            "Capitola Seahorses one, Aptos FC 0"
+        );
+        assert_eq!(
+            report.diagnostics[1].error,
+            LineError::Parse(ParseGameError::MissingSeparator {
+                line: 5,
+                column: 32
+            })
+        );
+    }
+
+    #[test]
+    fn strict_mode_stops_at_first_bad_line() {
+        let mut standings = Standings::default();
+        match ingest(INPUT.as_bytes(), &mut standings, Mode::Strict) {
+            Err(IngestError::Parse(err)) => assert_eq!(err.line(), 2),
+            other => panic!("expected parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn observers_see_every_matchday() {
+        let closed = Rc::new(RefCell::new(Vec::new()));
+        let mut standings = Standings::default();
+        let seen = Rc::clone(&closed);
+        standings.add_observer(move |event: &Event| {
+            if let Event::MatchdayClosed(snapshot) = event {
+                seen.borrow_mut().push(snapshot.matchday);
+            }
+        });
+        ingest(SAMPLE_INPUT.as_bytes(), &mut standings, Mode::Strict).unwrap();
+        assert_eq!(*closed.borrow(), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn ingest_str_matches_ingest() {
+        let (mut streamed, mut in_memory) = (Standings::default(), Standings::default());
+        ingest(SAMPLE_INPUT.as_bytes(), &mut streamed, Mode::Strict).unwrap();
+        ingest_str(SAMPLE_INPUT, &mut in_memory, Mode::Strict).unwrap();
+        assert_eq!(streamed.rankings(), in_memory.rankings());
+    }
+
+    #[test]
+    fn crlf_line_endings_are_stripped() {
+        let mut standings = Standings::default();
+        let input = "Aptos FC 2, Monterey United 0\r\nbroken\r\n";
+        let report = ingest(input.as_bytes(), &mut standings, Mode::Lenient).unwrap();
+        assert_eq!(report.ingested, 1);
+        assert_eq!(report.diagnostics[0].raw, "broken");
+        assert!(standings.record("Monterey United").is_some());
+    }
+
+    #[test]
+    fn out_of_order_markers_are_rejected() {
+        let mut standings = Standings::default();
+        let input = "# Matchday 5
+Aptos FC 1, Monterey United 0
+# Matchday 3
+Aptos FC 1, Felton Lumberjacks 0
+  [Round 5]
+Felton Lumberjacks 2, Monterey United 0
+";
+        let report = ingest_str(input, &mut standings, Mode::Lenient).unwrap();
+        assert_eq!(report.ingested, 3);
+        assert_eq!(
+            report
+                .diagnostics
+                .iter()
+                .map(|diagnostic| &diagnostic.error)
+                .collect::<Vec<_>>(),
+            vec![
+                &LineError::Rejected {
+                    line: 3,
+                    error: BookingError::MatchdayOutOfOrder(3)
+                },
+                &LineError::Rejected {
+                    line: 5,
+                    error: BookingError::MatchdayOutOfOrder(5)
+                }
+            ]
+        );
+        assert_eq!(
+            report.diagnostics[0].to_string(),
+            "line 3: matchday 3 cannot start after a later one (\"# Matchday 3\")"
+        );
+        let matchdays: Vec<usize> = standings.history().iter().map(|s| s.matchday).collect();
+        assert_eq!(matchdays, vec![5]);
+    }
+
+    #[test]
+    fn games_credited_to_future_matchdays_are_rejected() {
+        let mut standings = Standings::default();
+        let input = "Aptos FC 1, Monterey United 0
+Aptos FC 1, Felton Lumberjacks 0 (matchday 7)
+";
+        let report = ingest_str(input, &mut standings, Mode::Lenient).unwrap();
+        assert_eq!(report.ingested, 1);
+        assert_eq!(
+            report.diagnostics[0].error,
+            LineError::Rejected {
+                line: 2,
+                error: BookingError::FutureMatchday(7)
+            }
+        );
+        assert_eq!(
+            report.diagnostics[0].error.to_string(),
+            "line 2: matchday 7 has not started yet"
+        );
+        assert_eq!(standings.record("Aptos FC").unwrap().points, 3);
+    }
+
+    #[test]
+    fn aliases_merge_spellings_and_reject_unknown_teams() {
+        let mut aliases: Aliases = "San Jose Earthquakes = SJ Earthquakes\nAptos FC"
+            .parse()
+            .unwrap();
+        aliases.set_strict(true);
+        let mut standings = Standings::default();
+        standings.set_aliases(aliases).unwrap();
+        let input = "SJ Earthquakes 1, Aptos FC 0
+Aptos FC 2, San Jose  Earthquakes 2
+Aptos FC 1, Felton Lumberjacks 0
+";
+        let report = ingest(input.as_bytes(), &mut standings, Mode::Lenient).unwrap();
+        assert_eq!(report.ingested, 2);
+        assert_eq!(
+            report.diagnostics[0].error,
+            LineError::Parse(ParseGameError::UnknownTeam {
+                line: 3,
+                column: 13
+            })
+        );
+        assert_eq!(standings.record("San Jose Earthquakes").unwrap().played, 2);
+        assert!(standings.record("SJ Earthquakes").is_none());
+    }
+
+    #[test]
+    fn teams_outside_the_roster_are_malformed_lines() {
+        let mut standings = Standings::default();
+        standings
+            .set_aliases("Aptos FC = Aptos".parse().unwrap())
+            .unwrap();
+        let input = "# Teams
+Monterey United
+
+Aptos FC 1, Monterey United 0
+Monterey United 1, Aptos 0
+! Aptos -3
+";
+        let report = ingest_str(input, &mut standings, Mode::Lenient).unwrap();
+        assert_eq!(report.ingested, 0);
+        assert_eq!(
+            report
+                .diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.error.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                LineError::Parse(ParseGameError::UnknownTeam { line: 4, column: 1 }),
+                LineError::Parse(ParseGameError::UnknownTeam {
+                    line: 5,
+                    column: 20
+                }),
+                LineError::Parse(ParseGameError::UnknownTeam { line: 6, column: 3 })
+            ]
+        );
+        assert_eq!(report.idle_teams, vec!["Monterey United"]);
+    }
+
+    #[test]
+    fn teams_block_declares_the_roster() {
+        let mut standings = Standings::default();
+        let input = "# Teams
+Aptos FC = Aptos
+Monterey United
+Felton Lumberjacks
+
+Aptos 1, Monterey United 0
+Aptos FC 1, Watsonville Wolves 0
+";
+        let report = ingest_str(input, &mut standings, Mode::Lenient).unwrap();
+        assert_eq!(report.ingested, 1);
+        assert_eq!(
+            report.diagnostics[0].error,
+            LineError::Parse(ParseGameError::UnknownTeam {
+                line: 7,
+                column: 13
+            })
+        );
+        assert_eq!(report.idle_teams, vec!["Felton Lumberjacks"]);
+        let rankings = standings.rankings();
+        assert_eq!(rankings.len(), 3);
+        assert_eq!(rankings[1].team, "Felton Lumberjacks");
+        assert_eq!(rankings[1].record.played, 0);
+    }
+}
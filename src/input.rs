@@ -0,0 +1,410 @@
+use std::borrow::Cow;
+
+use crate::{Aliases, Game, GameRef, ParseGameError};
+
+/// A single line of the input format.
+///
+/// Besides games, the input may contain blank lines, comments starting with
+/// `#`, explicit matchday markers such as `# Matchday 5` or `[Round 5]` and
+/// commands starting with `!`: `! annul Home, Away`, `! withdraw Team` and
+/// point adjustments such as `! Derby County -21 "administration"`.
+///
+/// The input may start with a `# Teams` (or `[Teams]`) block declaring the
+/// league's teams, one per line and up to the next blank line, see
+/// `Line::parse_declaration`.
+///
+/// Like `Game`, a line owns its team names unless it was parsed with
+/// `Line::parse_ref`, which borrows them from the input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Line<T = String> {
+    Game(Game<T>),
+    Matchday(usize),            // the following games belong to this matchday
+    Annul { home: T, away: T }, // the last game between the two is annulled
+    Withdraw(T),                // the team withdrew, its results are expunged
+    Adjust { team: T, points: i64, reason: T }, // e.g. a points deduction
+    Teams,                      // starts the block of team declarations
+    Team { name: T, aliases: Vec<T> }, // a declared team and its other spellings
+    Comment,
+    Blank,
+}
+
+/// A line borrowing its team names from the parsed input.
+pub type LineRef<'a> = Line<&'a str>;
+
+impl Line {
+    /// Parses a single line of input, reporting errors against `line` (1-based).
+    pub fn parse(raw: &str, line: usize) -> Result<Line, ParseGameError> {
+        Line::parse_ref(raw, line).map(LineRef::into_owned)
+    }
+}
+
+impl<T> Line<T> {
+    // the teams a game or command refers to
+    pub(crate) fn teams(&self) -> impl Iterator<Item = &T> {
+        let (first, second) = match self {
+            Line::Game(game) => (Some(&game.home), Some(&game.away)),
+            Line::Annul { home, away } => (Some(home), Some(away)),
+            Line::Withdraw(team) | Line::Adjust { team, .. } => (Some(team), None),
+            _ => (None, None),
+        };
+        first.into_iter().chain(second)
+    }
+}
+
+impl<'a> Line<&'a str> {
+    /// Parses a single line of input without allocating, see `Line::parse`.
+    pub fn parse_ref(raw: &'a str, line: usize) -> Result<LineRef<'a>, ParseGameError> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            return Ok(Line::Blank);
+        }
+        let column = raw.len() - raw.trim_start().len() + 1;
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            if is_teams_header(comment) {
+                return Ok(Line::Teams);
+            }
+            return Ok(match matchday_marker(comment) {
+                Some(matchday) => Line::Matchday(matchday),
+                None => Line::Comment,
+            });
+        }
+        if let Some(bracketed) = trimmed.strip_prefix('[') {
+            let bracketed = bracketed.strip_suffix(']');
+            if bracketed.is_some_and(is_teams_header) {
+                return Ok(Line::Teams);
+            }
+            return match bracketed.and_then(matchday_marker) {
+                Some(matchday) => Ok(Line::Matchday(matchday)),
+                None => Err(ParseGameError::InvalidMarker { line, column }),
+            };
+        }
+        if let Some(command) = trimmed.strip_prefix('!') {
+            return parse_command(command.trim())
+                .ok_or(ParseGameError::InvalidCommand { line, column });
+        }
+        GameRef::parse_ref(raw, line).map(Line::Game)
+    }
+
+    /// Parses a line of the `# Teams` block: the name of a team, optionally
+    /// followed by `=` and its other spellings separated by commas, e.g.
+    /// `San Jose Earthquakes = SJ Earthquakes, San Jose`. Blank lines, comments,
+    /// markers and commands are parsed as by `Line::parse_ref`.
+    pub fn parse_declaration(raw: &'a str, line: usize) -> Result<LineRef<'a>, ParseGameError> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.starts_with(['#', '[', '!']) {
+            return Line::parse_ref(raw, line);
+        }
+        let (name, aliases) = match trimmed.find('=') {
+            Some(eq) => (&trimmed[..eq], Some(&trimmed[eq + 1..])),
+            None => (trimmed, None),
+        };
+        if name.contains(',') {
+            // most likely a game, the block was not closed by a blank line
+            return Err(ParseGameError::InvalidDeclaration {
+                line,
+                column: column_of(raw, name),
+            });
+        }
+        let aliases: Vec<&str> = aliases.into_iter().flat_map(|s| s.split(',')).collect();
+        for &name in std::iter::once(&name).chain(&aliases) {
+            if name.trim().is_empty() {
+                return Err(ParseGameError::MissingTeamName {
+                    line,
+                    column: column_of(raw, name),
+                });
+            }
+        }
+        Ok(Line::Team {
+            name: name.trim(),
+            aliases: aliases.into_iter().map(str::trim).collect(),
+        })
+    }
+
+    /// Maps every team name to its canonical spelling, see `Aliases::resolve`.
+    /// Names unknown to a strict roster are reported as
+    /// `ParseGameError::UnknownTeam` at their position in `raw`, the input the
+    /// line was parsed from. Names keep borrowing from `raw` unless their
+    /// spelling changes.
+    pub fn resolve(
+        self,
+        aliases: &Aliases,
+        raw: &'a str,
+        line: usize,
+    ) -> Result<Line<Cow<'a, str>>, ParseGameError> {
+        let resolve = |name: &'a str| {
+            aliases.resolve(name).ok_or(ParseGameError::UnknownTeam {
+                line,
+                column: column_of(raw, name),
+            })
+        };
+        Ok(match self {
+            Line::Game(game) => {
+                let (home, away) = (resolve(game.home)?, resolve(game.away)?);
+                Line::Game(Game {
+                    home,
+                    away,
+                    ..game.map_teams(Cow::Borrowed)
+                })
+            }
+            Line::Matchday(matchday) => Line::Matchday(matchday),
+            Line::Annul { home, away } => Line::Annul {
+                home: resolve(home)?,
+                away: resolve(away)?,
+            },
+            Line::Withdraw(team) => Line::Withdraw(resolve(team)?),
+            Line::Adjust {
+                team,
+                points,
+                reason,
+            } => Line::Adjust {
+                team: resolve(team)?,
+                points,
+                reason: Cow::Borrowed(reason),
+            },
+            Line::Teams => Line::Teams,
+            // declarations introduce the canonical names
+            Line::Team { name, aliases } => Line::Team {
+                name: Cow::Borrowed(name),
+                aliases: aliases.into_iter().map(Cow::Borrowed).collect(),
+            },
+            Line::Comment => Line::Comment,
+            Line::Blank => Line::Blank,
+        })
+    }
+
+    /// The same line owning its team names.
+    pub fn into_owned(self) -> Line {
+        match self {
+            Line::Game(game) => Line::Game(game.into_owned()),
+            Line::Matchday(matchday) => Line::Matchday(matchday),
+            Line::Annul { home, away } => Line::Annul {
+                home: home.to_string(),
+                away: away.to_string(),
+            },
+            Line::Withdraw(team) => Line::Withdraw(team.to_string()),
+            Line::Adjust {
+                team,
+                points,
+                reason,
+            } => Line::Adjust {
+                team: team.to_string(),
+                points,
+                reason: reason.to_string(),
+            },
+            Line::Teams => Line::Teams,
+            Line::Team { name, aliases } => Line::Team {
+                name: name.to_string(),
+                aliases: aliases.into_iter().map(str::to_string).collect(),
+            },
+            Line::Comment => Line::Comment,
+            Line::Blank => Line::Blank,
+        }
+    }
+}
+
+// 1-based column of `name` within `raw`, or 1 if it was not taken from `raw`
+pub(crate) fn column_of(raw: &str, name: &str) -> usize {
+    (name.as_ptr() as usize)
+        .checked_sub(raw.as_ptr() as usize)
+        .filter(|&offset| offset <= raw.len())
+        .map_or(1, |offset| offset + 1)
+}
+
+// "annul Home, Away", "withdraw Team" or "Team -21 \"reason\""
+fn parse_command(s: &str) -> Option<LineRef<'_>> {
+    let (keyword, rest) = match s.find(' ') {
+        Some(space) => (&s[..space], s[space + 1..].trim()),
+        None => (s, ""),
+    };
+    if keyword.eq_ignore_ascii_case("annul") {
+        let sep = rest.find(',')?;
+        let (home, away) = (rest[..sep].trim(), rest[sep + 1..].trim());
+        if home.is_empty() || away.is_empty() {
+            return None;
+        }
+        return Some(Line::Annul { home, away });
+    }
+    if keyword.eq_ignore_ascii_case("withdraw") && !rest.is_empty() {
+        return Some(Line::Withdraw(rest));
+    }
+    parse_adjustment(s)
+}
+
+// "Team -21" with an optional quoted reason, the sign is required
+fn parse_adjustment(s: &str) -> Option<LineRef<'_>> {
+    let (s, reason) = match s.find('"') {
+        Some(quote) => {
+            let reason = s[quote + 1..].strip_suffix('"')?;
+            if reason.contains('"') {
+                return None;
+            }
+            (s[..quote].trim_end(), reason)
+        }
+        None => (s, ""),
+    };
+    let space = s.rfind(' ')?;
+    let (team, points) = (s[..space].trim(), &s[space + 1..]);
+    if team.is_empty() || !(points.starts_with('-') || points.starts_with('+')) {
+        return None;
+    }
+    Some(Line::Adjust {
+        team,
+        points: points.parse().ok()?,
+        reason,
+    })
+}
+
+// "Teams", case-insensitive
+fn is_teams_header(s: &str) -> bool {
+    s.trim().eq_ignore_ascii_case("teams")
+}
+
+// "Matchday 5" or "Round 5", keyword case-insensitive
+fn matchday_marker(s: &str) -> Option<usize> {
+    let mut words = s.split_whitespace();
+    let keyword = words.next()?;
+    if !keyword.eq_ignore_ascii_case("matchday") && !keyword.eq_ignore_ascii_case("round") {
+        return None;
+    }
+    match (words.next(), words.next()) {
+        (Some(number), None) => number.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_recognizes_markers_and_comments() {
+        assert_eq!(Line::parse("# Matchday 5", 1), Ok(Line::Matchday(5)));
+        assert_eq!(Line::parse("[Round 12]", 1), Ok(Line::Matchday(12)));
+        assert_eq!(Line::parse("  # round 2  ", 1), Ok(Line::Matchday(2)));
+        assert_eq!(Line::parse("# exported 2020-01-05", 1), Ok(Line::Comment));
+        assert_eq!(Line::parse("# Matchday five", 1), Ok(Line::Comment));
+        assert_eq!(Line::parse("   ", 1), Ok(Line::Blank));
+        assert_eq!(
+            Line::parse("Aptos FC 2, Monterey United 0", 1),
+            Ok(Line::Game("Aptos FC 2, Monterey United 0".parse().unwrap()))
+        );
+    }
+
+    #[test]
+    fn parse_rejects_broken_brackets() {
+        assert_eq!(
+            Line::parse("  [Round 5", 3),
+            Err(ParseGameError::InvalidMarker { line: 3, column: 3 })
+        );
+        assert_eq!(
+            Line::parse("[Week 5]", 4),
+            Err(ParseGameError::InvalidMarker { line: 4, column: 1 })
+        );
+    }
+
+    #[test]
+    fn parse_recognizes_commands() {
+        assert_eq!(
+            Line::parse("! annul Aptos FC, Monterey United", 1),
+            Ok(Line::Annul {
+                home: "Aptos FC".to_string(),
+                away: "Monterey United".to_string()
+            })
+        );
+        assert_eq!(
+            Line::parse("!withdraw Felton Lumberjacks ", 1),
+            Ok(Line::Withdraw("Felton Lumberjacks".to_string()))
+        );
+        assert_eq!(
+            Line::parse("! Derby County -21 \"administration\"", 1),
+            Ok(Line::Adjust {
+                team: "Derby County".to_string(),
+                points: -21,
+                reason: "administration".to_string()
+            })
+        );
+        assert_eq!(
+            Line::parse("! Derby County +3", 1),
+            Ok(Line::Adjust {
+                team: "Derby County".to_string(),
+                points: 3,
+                reason: String::new()
+            })
+        );
+        assert_eq!(
+            Line::parse("! Derby County 21", 1),
+            Err(ParseGameError::InvalidCommand { line: 1, column: 1 })
+        );
+        assert_eq!(
+            Line::parse(" ! annul Aptos FC", 2),
+            Err(ParseGameError::InvalidCommand { line: 2, column: 2 })
+        );
+    }
+
+    #[test]
+    fn parse_ref_borrows_from_input() {
+        let raw = "! annul Aptos FC, Monterey United";
+        match Line::parse_ref(raw, 1) {
+            Ok(Line::Annul { home, away }) => {
+                assert!(std::ptr::eq(home, &raw[8..16]));
+                assert_eq!(away, "Monterey United");
+            }
+            other => panic!("expected annulment, got {:?}", other),
+        }
+        assert_eq!(
+            Line::parse_ref(raw, 1).map(LineRef::into_owned),
+            Line::parse(raw, 1)
+        );
+    }
+
+    #[test]
+    fn resolve_reports_unknown_teams() {
+        let mut aliases: Aliases = "Aptos FC = Aptos\nMonterey United".parse().unwrap();
+        let raw = "Aptos 2, Monterey United 0";
+        match Line::parse_ref(raw, 1).unwrap().resolve(&aliases, raw, 1) {
+            Ok(Line::Game(game)) => {
+                assert_eq!(game.home, "Aptos FC");
+                assert!(matches!(game.away, Cow::Borrowed("Monterey United")));
+            }
+            other => panic!("expected game, got {:?}", other),
+        }
+
+        aliases.set_strict(true);
+        let raw = "! withdraw  Felton";
+        assert_eq!(
+            Line::parse_ref(raw, 3).unwrap().resolve(&aliases, raw, 3),
+            Err(ParseGameError::UnknownTeam {
+                line: 3,
+                column: 13
+            })
+        );
+    }
+
+    #[test]
+    fn parse_declaration_reads_names_and_aliases() {
+        assert_eq!(Line::parse("[Teams]", 1), Ok(Line::Teams));
+        assert_eq!(Line::parse("# teams ", 1), Ok(Line::Teams));
+        assert_eq!(
+            Line::parse_declaration(" San Jose Earthquakes = SJ Earthquakes, San Jose", 2),
+            Ok(Line::Team {
+                name: "San Jose Earthquakes",
+                aliases: vec!["SJ Earthquakes", "San Jose"]
+            })
+        );
+        assert_eq!(
+            Line::parse_declaration("# Matchday 1", 3),
+            Ok(Line::Matchday(1))
+        );
+        assert_eq!(
+            Line::parse_declaration("Aptos FC = SJ,", 4),
+            Err(ParseGameError::MissingTeamName {
+                line: 4,
+                column: 15
+            })
+        );
+        assert_eq!(
+            Line::parse_declaration("Aptos FC 1, Monterey United 0", 5),
+            Err(ParseGameError::InvalidDeclaration { line: 5, column: 1 })
+        );
+    }
+}
@@ -1,210 +1,1501 @@
-use std::cmp::Ordering;
 // There are faster hashing functions other than Rust's built-ins
 // Both fnv and fx could be good alternatives, but this should be good enough
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::io::{self, Write};
 
-#[derive(Debug, PartialEq)]
-pub enum Outcome<'a> {
-    WINLOSS((&'a str, &'a str)), // tuple of winner, loser
-    DRAW((&'a str, &'a str)),
-}
-
-// Refactor-NOTE
-// Instead of handling Strings for team names, we could use a hashbag for space-savings.
-// Scores could also be made up of more detailed data, such as vectors of tuples of (playername, minute scored).
+use crate::observer::Observers;
 
-pub struct Game {
-    home_name: String,
-    home_score: u8,
-    away_name: String,
-    away_score: u8,
-}
+mod adjustment;
+mod alias;
+mod error;
+mod format;
+mod game;
+mod history;
+pub mod ingest;
+mod input;
+mod observer;
+mod record;
+mod scoring;
+mod snapshot;
+mod stats;
+mod team;
+mod tiebreak;
 
-impl Game {
-    // Refactor-TODO: implement FromStr Trait instead
-    pub fn from_str(raw: &str) -> Result<Game, String> {
-        // NOTE: assuming "{home name} {home score}, {away name} {away score}" format.
-        // If the input format cannot be guaranteed, this will be the place to adjust.
-        let v: Vec<&str> = raw.split(", ").collect();
-        if v.len() != 2 {
-            return Err(format!("No game data found in line {}", raw));
-        }
-        let h: Vec<&str> = v[0].rsplitn(2, ' ').collect();
-        let a: Vec<&str> = v[1].rsplitn(2, ' ').collect();
-        Ok(Game {
-            home_name: h[1].to_string(),
-            home_score: h[0].parse().unwrap(),
-            away_name: a[1].to_string(),
-            away_score: a[0].parse().unwrap(),
-        })
-    }
+pub use crate::adjustment::Adjustment;
+pub use crate::alias::Aliases;
+pub use crate::error::{
+    BookingError, IngestError, LineError, OverflowError, ParseAliasesError, ParseGameError,
+    ParseScoringError, ParseTiebreakerError,
+};
+pub use crate::format::{PositionStyle, Printer};
+pub use crate::game::{Decision, Game, GameRef, Outcome};
+pub use crate::history::{Fixture, LoggedGame, MatchResult, TimelineEntry};
+pub use crate::input::{Line, LineRef};
+pub use crate::observer::{Event, Observer};
+pub use crate::record::TeamRecord;
+pub use crate::scoring::{Bonus, ScoringRules};
+pub use crate::snapshot::{MatchdaySnapshot, Movement, Note, RankedEntry};
+pub use crate::stats::{Stats, Streak, StreakKind};
+pub use crate::team::{TeamId, Teams};
+pub use crate::tiebreak::Tiebreaker;
 
-    pub fn outcome(&self) -> Outcome {
-        match self.home_score.cmp(&self.away_score) {
-            Ordering::Greater => Outcome::WINLOSS((&self.home_name, &self.away_name)),
-            Ordering::Less => Outcome::WINLOSS((&self.away_name, &self.home_name)),
-            Ordering::Equal => Outcome::DRAW((&self.home_name, &self.away_name)),
-        }
-    }
+// a game as booked in `Standings`
+#[derive(Debug, Clone, PartialEq)]
+struct Booked {
+    game: Game<TeamId>,
+    position: usize, // number of games ingested before this one
+    matchday: usize, // the matchday the game is credited to
+    played: usize,   // the matchday during which it was ingested
 }
 
 #[derive(Debug)]
 pub struct Standings {
-    teams_with_points: HashMap<String, u8>,
-    tmp_teams_with_games: HashSet<String>, // temporary set to determine whether a new matchday has started
+    teams: Teams,        // every team name seen, games and records refer to them by id
+    aliases: Aliases,    // canonical spellings of team names, applied to every name passed in
+    roster: Vec<TeamId>, // the declared teams, if any; others are rejected
+    records: HashMap<TeamId, TeamRecord>,
+    games: Vec<Booked>, // every ingested game, for head-to-head comparisons and recomputation
+    ingested: usize,    // number of games ingested, including expunged ones
+    reapply_head_to_head: bool, // UEFA style: restart head-to-head among teams still level
+    fair_play: HashMap<TeamId, u32>, // disciplinary points, lower is better
+    tiebreakers: Vec<Tiebreaker>, // applied in order to teams level on points
+    tmp_teams_with_games: HashSet<TeamId>, // temporary set to determine whether a new matchday has started
     // (we're expexting to have every team play once during a matchday)
-    win_points: u8,   // points the winner gets
-    draw_points: u8,  // points for a draw for both teams,
-    print_top: usize, // prints the top-ranking n teams
-    matchday: usize,  // current matchday
+    explicit_matchdays: bool, // set by the first matchday marker, turns off the inference above
+    scoring: ScoringRules,    // points per result
+    print_top: usize,         // prints the top-ranking n teams
+    form_length: usize,       // number of results in `RankedEntry::form`
+    matchday: usize,          // current matchday
+    history: Vec<MatchdaySnapshot>, // every closed matchday, revised ones replaced
+    awarded: HashSet<TeamId>, // teams with an awarded result among the booked games
+    annulled: HashMap<TeamId, usize>, // per team, the earliest matchday of a game removed by `annul`
+    withdrawn: Vec<TeamId>,           // teams whose results are expunged
+    expunged: HashMap<(TeamId, TeamId), usize>, // per team and withdrawn opponent, the earliest matchday of an expunged game
+    adjustments: Vec<Adjustment<TeamId>>,       // point deductions and the like, in order
+    observers: Observers,
 }
 
 impl Default for Standings {
     fn default() -> Self {
         Standings {
-            teams_with_points: Default::default(),
+            teams: Default::default(),
+            aliases: Default::default(),
+            roster: Default::default(),
+            records: Default::default(),
+            games: Default::default(),
+            ingested: 0,
+            reapply_head_to_head: false,
+            fair_play: Default::default(),
+            tiebreakers: Default::default(),
             tmp_teams_with_games: Default::default(),
-            win_points: 3,
-            draw_points: 1,
+            explicit_matchdays: false,
+            scoring: Default::default(),
             print_top: 3,
+            form_length: 0,
             matchday: 1,
+            history: Default::default(),
+            awarded: Default::default(),
+            annulled: Default::default(),
+            withdrawn: Default::default(),
+            expunged: Default::default(),
+            adjustments: Default::default(),
+            observers: Default::default(),
         }
     }
 }
 
 impl Standings {
-    pub fn new(win_points: u8, draw_points: u8, print_top: usize) -> Standings {
+    /// Standings for the common win/draw/loss scoring, see `set_scoring` for others.
+    pub fn new(win_points: i64, draw_points: i64, print_top: usize) -> Standings {
         Standings {
-            win_points,
-            draw_points,
+            scoring: ScoringRules::win_draw(win_points, draw_points),
             print_top,
             ..Default::default()
         }
     }
 
-    pub fn print_rankings(&self) {
-        if !self.teams_with_points.is_empty() {
-            let mut v: Vec<(&String, &u8)> = self.teams_with_points.iter().collect();
-            v.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
-            println!("Matchday {}", self.matchday);
-            for item in v.iter().take(self.print_top) {
-                println!("{}, {} pt{}", item.0, item.1, pluralize(*item.1));
+    /// Sets the points awarded per result. Applies to games ingested afterwards.
+    pub fn set_scoring(&mut self, scoring: ScoringRules) {
+        self.scoring = scoring;
+    }
+
+    /// Adds `aliases` to the table mapping the spellings of team names to
+    /// canonical ones, see `Aliases::merge`; teams declared with `declare_team`
+    /// keep their spellings. The names passed to `ingest`, `annul`, `withdraw`
+    /// and `adjust` are looked up in the table.
+    ///
+    /// A strict table (see `Aliases::set_strict`) is a roster: every team in
+    /// it is declared as with `declare_team`.
+    pub fn set_aliases(&mut self, aliases: Aliases) -> Result<(), ParseAliasesError> {
+        self.aliases.merge(&aliases)?;
+        if aliases.is_strict() {
+            let mut teams: Vec<&str> = aliases.teams().collect();
+            teams.sort_unstable();
+            for team in teams {
+                self.enroll(team);
             }
         }
+        Ok(())
+    }
+
+    /// Declares `team` as part of the league, known under `aliases` as well.
+    /// It is listed with 0 points until it plays. Once a team has been
+    /// declared, games, withdrawals and adjustments involving teams that
+    /// have not are rejected with `BookingError::UnknownTeam`. The spellings
+    /// are added to the table of `set_aliases`, which becomes strict.
+    pub fn declare_team<'a, I>(&mut self, team: &str, aliases: I) -> Result<(), ParseAliasesError>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        self.aliases.add_team(team)?;
+        for alias in aliases {
+            self.aliases.add_alias(team, alias)?;
+        }
+        self.aliases.set_strict(true);
+        let canonical = self.aliases.resolve(team).unwrap_or(Cow::Borrowed(team));
+        self.enroll(&canonical);
+        Ok(())
+    }
+
+    /// The declared teams, see `declare_team`.
+    pub fn roster(&self) -> impl Iterator<Item = &str> {
+        self.roster.iter().map(move |&team| self.teams.name(team))
+    }
+
+    /// Declared teams that have not played a game yet, e.g. because they are
+    /// misspelled in every result.
+    pub fn idle_teams(&self) -> Vec<&str> {
+        self.roster
+            .iter()
+            .filter(|team| {
+                self.records
+                    .get(team)
+                    .is_some_and(|record| record.played == 0)
+            })
+            .map(|&team| self.teams.name(team))
+            .collect()
+    }
+
+    pub fn aliases(&self) -> &Aliases {
+        &self.aliases
+    }
+
+    /// Sets the criteria that separate teams level on points, in order of precedence.
+    /// Teams still level after the last one are ordered alphabetically.
+    pub fn set_tiebreakers(&mut self, tiebreakers: Vec<Tiebreaker>) {
+        self.tiebreakers = tiebreakers;
+    }
+
+    /// Makes consecutive head-to-head tiebreakers work as one mini-league (UEFA style):
+    /// if they separate some of the tied teams but leave a smaller group level,
+    /// they are applied again using only the games among that group.
+    pub fn set_head_to_head_reapplied(&mut self, reapply: bool) {
+        self.reapply_head_to_head = reapply;
+    }
+
+    /// Makes the entries of rankings and snapshots carry the results of each
+    /// team's last `n` games as `form`. Off (0) by default.
+    pub fn set_form_length(&mut self, n: usize) {
+        self.form_length = n;
+    }
+
+    /// Adds disciplinary points for `Tiebreaker::FairPlay`.
+    pub fn add_fair_play_points(&mut self, team: &str, points: u32) {
+        let team = self.teams.intern(team);
+        *self.fair_play.entry(team).or_insert(0) += points;
+    }
+
+    /// The record of a single team, if it has played already or is declared.
+    pub fn record(&self, name: &str) -> Option<&TeamRecord> {
+        self.teams.id(name).and_then(|team| self.records.get(&team))
+    }
+
+    /// The registry of all team names seen so far.
+    pub fn teams(&self) -> &Teams {
+        &self.teams
+    }
+
+    /// All teams in ranking order with their position. Teams that could not
+    /// be separated by points and tiebreakers share a position ("1, 1, 3").
+    pub fn rankings(&self) -> Vec<RankedEntry> {
+        let ranked = tiebreak::rank(self);
+        let mut form = self.form_guide(self.form_length);
+        ranked
+            .iter()
+            .map(|&(position, team)| RankedEntry {
+                position,
+                shared: ranked.iter().filter(|(pos, _)| *pos == position).count() > 1,
+                team: self.teams.name(team).to_string(),
+                record: self.records[&team].clone(),
+                notes: self.notes(team),
+                movement: None,
+                form: form.remove(&team).unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// The rankings as of the current matchday, with every team's movement since
+    /// the matchday before.
+    pub fn snapshot(&self) -> MatchdaySnapshot {
+        let mut snapshot = MatchdaySnapshot {
+            matchday: self.matchday,
+            entries: self.rankings(),
+            late_games: Vec::new(),
+        };
+        snapshot.compare_with(self.closed_before(self.matchday));
+        snapshot
     }
 
-    pub fn ingest(&mut self, game: Game) {
-        // check if a new matchday has started
-        if self.tmp_teams_with_games.contains(&game.home_name)
-            || self.tmp_teams_with_games.contains(&game.away_name)
+    /// The rankings as they stand for an earlier `matchday`, recomputed from all
+    /// games credited to it or to a matchday before, including games that were
+    /// played out of order after `matchday` had been closed.
+    pub fn snapshot_at(&self, matchday: usize) -> Result<MatchdaySnapshot, OverflowError> {
+        let mut past = Standings {
+            teams: self.teams.clone(),
+            roster: self.roster.clone(),
+            scoring: self.scoring.clone(),
+            print_top: self.print_top,
+            tiebreakers: self.tiebreakers.clone(),
+            reapply_head_to_head: self.reapply_head_to_head,
+            fair_play: self.fair_play.clone(),
+            form_length: self.form_length,
+            // only what happened to games up to `matchday`
+            annulled: self
+                .annulled
+                .iter()
+                .filter(|(_, &earliest)| earliest <= matchday)
+                .map(|(&team, &earliest)| (team, earliest))
+                .collect(),
+            withdrawn: self.withdrawn.clone(),
+            expunged: self
+                .expunged
+                .iter()
+                .filter(|(_, &earliest)| earliest <= matchday)
+                .map(|(&teams, &earliest)| (teams, earliest))
+                .collect(),
+            adjustments: self
+                .adjustments
+                .iter()
+                .filter(|adjustment| adjustment.matchday <= matchday)
+                .cloned()
+                .collect(),
+            matchday,
+            ..Default::default()
+        };
+        past.games = self
+            .games
+            .iter()
+            .filter(|booked| booked.matchday <= matchday)
+            .cloned()
+            .collect();
+        past.recount()?;
+        let late_games = past
+            .games
+            .iter()
+            .filter(|booked| booked.played > matchday)
+            .map(|booked| self.named(&booked.game))
+            .collect();
+        let mut snapshot = MatchdaySnapshot {
+            late_games,
+            ..past.snapshot()
+        };
+        snapshot.compare_with(self.closed_before(matchday));
+        Ok(snapshot)
+    }
+
+    /// A table of each team's last `n` games only, ranked by the points
+    /// earned in them. Adjustments are not taken into account, the `form` of
+    /// every entry holds the results of those games.
+    pub fn form_table(&self, n: usize) -> Result<MatchdaySnapshot, OverflowError> {
+        let mut recent = Standings {
+            teams: self.teams.clone(),
+            scoring: self.scoring.clone(),
+            tiebreakers: self.tiebreakers.clone(),
+            reapply_head_to_head: self.reapply_head_to_head,
+            fair_play: self.fair_play.clone(),
+            matchday: self.matchday,
+            ..Default::default()
+        };
+        for &team in &self.roster {
+            if !self.is_withdrawn(team) {
+                recent.records.insert(team, TeamRecord::default());
+            }
+        }
+        for booked in self.by_matchday().into_iter().rev() {
+            let game = &booked.game;
+            let (home_points, away_points) = self.points(game);
+            let sides = [
+                (
+                    game.home,
+                    game.home_score,
+                    game.away_score,
+                    game.home_result(),
+                    home_points,
+                    false,
+                ),
+                (
+                    game.away,
+                    game.away_score,
+                    game.home_score,
+                    game.home_result().reverse(),
+                    away_points,
+                    true,
+                ),
+            ];
+            let mut counted = false;
+            for (team, scored, conceded, result, points, away) in sides {
+                let record = recent.records.entry(team).or_default();
+                if record.played as usize >= n {
+                    continue;
+                }
+                *record = record
+                    .with_game(scored, conceded, result, points, away)
+                    .ok_or_else(|| OverflowError {
+                        team: self.teams.name(team).to_string(),
+                    })?;
+                counted = true;
+            }
+            if counted {
+                if game.is_awarded() {
+                    recent.awarded.extend([game.home, game.away]);
+                }
+                recent.games.push(booked.clone());
+            }
+        }
+        recent.games.reverse();
+        // the form of each team, not of the games kept for head-to-head comparisons
+        let mut form = self.form_guide(n);
+        let mut snapshot = recent.snapshot();
+        for entry in &mut snapshot.entries {
+            let team = self.teams.id(&entry.team).expect("ranked team is interned");
+            entry.form = form.remove(&team).unwrap_or_default();
+        }
+        Ok(snapshot)
+    }
+
+    /// The snapshot of every closed matchday, in the order they were closed.
+    /// A matchday revised by late games is represented by its latest snapshot.
+    pub fn history(&self) -> &[MatchdaySnapshot] {
+        &self.history
+    }
+
+    /// The position and points of `team` after each closed matchday it had
+    /// a place in the table, e.g. to find the first matchday it led the league.
+    pub fn timeline(&self, team: &str) -> Vec<TimelineEntry> {
+        self.history
+            .iter()
+            .filter_map(|snapshot| {
+                let entry = snapshot.entry(team)?;
+                Some(TimelineEntry {
+                    matchday: snapshot.matchday,
+                    position: entry.position,
+                    shared: entry.shared,
+                    points: entry.record.points,
+                })
+            })
+            .collect()
+    }
+
+    /// Writes the top-ranking teams of the current matchday to `w`, as many
+    /// as given to `new`. Use a `Printer` for any other layout.
+    pub fn write_rankings<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let printer = Printer {
+            top: self.print_top,
+            ..Default::default()
+        };
+        printer.write_rankings(w, &self.snapshot())
+    }
+
+    /// Registers an observer that is notified of every `Event`, e.g. each time
+    /// a matchday is closed.
+    pub fn add_observer<O: Observer + 'static>(&mut self, observer: O) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Closes the current matchday at the end of the input, so observers see
+    /// the last matchday as well. Returns its snapshot, or `None` if no game has
+    /// been played in it. Games ingested afterwards start the next matchday.
+    pub fn finish(&mut self) -> Option<MatchdaySnapshot> {
+        if self.tmp_teams_with_games.is_empty() {
+            return None;
+        }
+        let closed = self.close_matchday();
+        self.tmp_teams_with_games.clear();
+        Some(closed)
+    }
+
+    /// Starts `matchday` as announced by a marker in the input, closing the
+    /// current matchday first if any game has been played in it. Returns the
+    /// snapshot of the closed matchday.
+    ///
+    /// Once a matchday has been started explicitly, new matchdays are no longer
+    /// inferred from a team playing twice, so byes, odd team counts and teams
+    /// playing twice in a round are handled correctly.
+    ///
+    /// Matchdays must be started in ascending order: fails with
+    /// `BookingError::MatchdayOutOfOrder` if a game has been played on
+    /// `matchday` or a later one already. Late games are credited to earlier
+    /// matchdays with `Game::with_matchday` instead.
+    pub fn start_matchday(
+        &mut self,
+        matchday: usize,
+    ) -> Result<Option<MatchdaySnapshot>, BookingError> {
+        let latest = if self.tmp_teams_with_games.is_empty() {
+            self.history.last().map_or(0, |closed| closed.matchday)
+        } else {
+            self.matchday
+        };
+        if matchday <= latest {
+            return Err(BookingError::MatchdayOutOfOrder(matchday));
+        }
+        self.explicit_matchdays = true;
+        let closed = self.finish();
+        self.matchday = matchday;
+        Ok(closed)
+    }
+
+    /// Books `game`. Returns the final snapshot of the previous matchday if
+    /// `game` is the first one of a new matchday.
+    ///
+    /// A game tagged with an earlier matchday (see `Game::with_matchday`) is
+    /// credited to that matchday without starting a new one, and observers get
+    /// an `Event::MatchdayRevised` for every closed matchday it changes.
+    ///
+    /// Fails without booking `game` if a team's goals or points would overflow,
+    /// if a team is not on the roster or if `game` is tagged with a matchday
+    /// after the current one.
+    pub fn ingest<T: AsRef<str>>(
+        &mut self,
+        game: Game<T>,
+    ) -> Result<Option<MatchdaySnapshot>, BookingError> {
+        let game = game.as_ref().map_teams(|name| name.as_ref());
+        let home = self.canonical(game.home)?;
+        let away = self.canonical(game.away)?;
+        self.book(Game {
+            home: &home,
+            away: &away,
+            ..game
+        })
+    }
+
+    // books `game` once its teams have been resolved
+    fn book(&mut self, game: Game<&str>) -> Result<Option<MatchdaySnapshot>, BookingError> {
+        // a game after `finish` starts the next matchday
+        let current = if self.is_closed() {
+            self.matchday + 1
+        } else {
+            self.matchday
+        };
+        if let Some(matchday) = game.matchday.filter(|&matchday| matchday > current) {
+            return Err(BookingError::FutureMatchday(matchday));
+        }
+        let withdrawn = |name: &str| {
+            self.teams
+                .id(name)
+                .is_some_and(|team| self.is_withdrawn(team))
+        };
+        if withdrawn(game.home) || withdrawn(game.away) {
+            let teams = &mut self.teams;
+            let game = game.map_teams(|name| teams.intern(name));
+            self.expunge(&game, game.matchday.unwrap_or(current));
+            self.ingested += 1;
+            return Ok(None);
+        }
+        // new teams are only interned once the game is sure to be booked
+        let records = self.credited(&game, |name| self.teams.id(name), |name| name.to_string())?;
+        let teams = &mut self.teams;
+        let game = game.map_teams(|name| teams.intern(name));
+        let position = self.ingested;
+        self.ingested += 1;
+        self.matchday = current;
+        let mut closed = None;
+        // check if a new matchday has started, unless the input tells us
+        if game.matchday.is_none()
+            && !self.explicit_matchdays
+            && (self.tmp_teams_with_games.contains(&game.home)
+                || self.tmp_teams_with_games.contains(&game.away))
         {
             // it's a new day!
-            self.print_rankings();
-            println!(); // separator between matchdays, but not at the end of program
+            closed = Some(self.close_matchday());
             self.tmp_teams_with_games.clear();
             self.matchday += 1;
         }
 
-        match game.outcome() {
-            Outcome::WINLOSS((winner, loser)) => {
-                self.add_points_to_team(winner, self.win_points);
-                self.add_points_to_team(loser, 0); // important if printing of rankings cannot be filled by teams who have earned wins
+        let matchday = game.matchday.unwrap_or(self.matchday);
+        if matchday == self.matchday {
+            // add both teams to seen teams for current matchday
+            self.tmp_teams_with_games.insert(game.home);
+            self.tmp_teams_with_games.insert(game.away);
+        }
+        self.records.insert(game.home, records.0);
+        self.records.insert(game.away, records.1);
+        if game.is_awarded() {
+            self.awarded.extend([game.home, game.away]);
+        }
+        self.games.push(Booked {
+            game,
+            position,
+            matchday,
+            played: self.matchday,
+        });
+        if matchday < self.matchday {
+            self.revise(matchday)?;
+        }
+        Ok(closed)
+    }
+
+    /// Annuls the most recent game of `home` against `away`, as if it had never
+    /// been played. Returns whether there was such a game.
+    pub fn annul(&mut self, home: &str, away: &str) -> Result<bool, OverflowError> {
+        let id = |team| {
+            let team = self.aliases.resolve(team)?;
+            self.teams.id(&team)
+        };
+        let (home, away) = match (id(home), id(away)) {
+            (Some(home), Some(away)) => (home, away),
+            _ => return Ok(false),
+        };
+        let found = self
+            .games
+            .iter()
+            .rposition(|booked| booked.game.home == home && booked.game.away == away);
+        match found {
+            Some(idx) => {
+                let booked = self.games.remove(idx);
+                for team in [home, away] {
+                    let earliest = self.annulled.entry(team).or_insert(booked.matchday);
+                    *earliest = (*earliest).min(booked.matchday);
+                }
+                self.recount()?;
+                Ok(true)
             }
-            Outcome::DRAW((home, away)) => {
-                self.add_points_to_team(home, self.draw_points);
-                self.add_points_to_team(away, self.draw_points);
+            None => Ok(false),
+        }
+    }
+
+    /// Withdraws `team` from the competition: its record and all its games are
+    /// expunged, so its opponents lose the points they earned against it.
+    /// Games of `team` ingested later are ignored.
+    pub fn withdraw(&mut self, team: &str) -> Result<(), BookingError> {
+        let team = self.canonical(team)?;
+        let team = self.teams.intern(&team);
+        if self.is_withdrawn(team) {
+            return Ok(());
+        }
+        self.withdrawn.push(team);
+        let (expunged, kept) = std::mem::take(&mut self.games)
+            .into_iter()
+            .partition(|booked| booked.game.home == team || booked.game.away == team);
+        let expunged: Vec<Booked> = expunged;
+        for booked in &expunged {
+            self.expunge(&booked.game, booked.matchday);
+        }
+        self.games = kept;
+        Ok(self.recount()?)
+    }
+
+    /// Adds `points` (negative for a deduction) to the record of `team` as of
+    /// the current matchday, e.g. `standings.adjust("Derby County", -21, "administration")`.
+    pub fn adjust(&mut self, team: &str, points: i64, reason: &str) -> Result<(), BookingError> {
+        let team = self.canonical(team)?;
+        let adjustment = Adjustment {
+            team: self.teams.intern(&team),
+            points,
+            reason: reason.to_string(),
+            matchday: self.matchday,
+        };
+        if !self.is_withdrawn(adjustment.team) {
+            self.apply(&adjustment)?;
+        }
+        self.adjustments.push(adjustment);
+        Ok(())
+    }
+
+    /// Every booked game in the order it was ingested. Annulled games and
+    /// games of withdrawn teams are not part of the log.
+    pub fn log(&self) -> impl Iterator<Item = LoggedGame<'_>> {
+        self.games.iter().map(move |booked| LoggedGame {
+            game: booked.game.clone().map_teams(|team| self.teams.name(team)),
+            position: booked.position,
+            matchday: booked.matchday,
+            played: booked.played,
+        })
+    }
+
+    /// The booked games of `team` by matchday, games credited to the same
+    /// matchday in the order they were ingested. Empty for unknown teams.
+    pub fn fixtures(&self, team: &str) -> Vec<Fixture<'_>> {
+        let id = match self.teams.id(team) {
+            Some(id) => id,
+            None => return Vec::new(),
+        };
+        let mut fixtures: Vec<Fixture> = self
+            .games
+            .iter()
+            .filter(|booked| booked.game.home == id || booked.game.away == id)
+            .map(|booked| {
+                let (home_points, away_points) = self.points(&booked.game);
+                Fixture {
+                    team: self.teams.name(id),
+                    game: booked.game.clone().map_teams(|team| self.teams.name(team)),
+                    position: booked.position,
+                    matchday: booked.matchday,
+                    points: if booked.game.home == id {
+                        home_points
+                    } else {
+                        away_points
+                    },
+                }
+            })
+            .collect();
+        // stable, late games keep their order within the matchday
+        fixtures.sort_by_key(|fixture| fixture.matchday);
+        fixtures
+    }
+
+    /// The results of `team` in the order of `fixtures`.
+    pub fn results(&self, team: &str) -> Vec<MatchResult> {
+        self.fixtures(team).iter().map(Fixture::result).collect()
+    }
+
+    /// The longest streaks, biggest wins and highest-scoring games of the
+    /// league, derived from the booked games.
+    pub fn stats(&self) -> Stats {
+        let mut teams: Vec<&str> = self
+            .records
+            .keys()
+            .map(|&team| self.teams.name(team))
+            .collect();
+        teams.sort_unstable();
+        Stats::collect(None, teams.into_iter().map(|team| self.fixtures(team)))
+    }
+
+    /// Like `stats`, for the games of a single team. `None` for unknown teams.
+    pub fn team_stats(&self, team: &str) -> Option<Stats> {
+        self.teams.id(team)?;
+        Some(Stats::collect(
+            Some(team.to_string()),
+            Some(self.fixtures(team)),
+        ))
+    }
+
+    /// All point adjustments so far, in the order they were made.
+    pub fn adjustments(&self) -> Vec<Adjustment> {
+        self.adjustments
+            .iter()
+            .map(|adjustment| Adjustment {
+                team: self.teams.name(adjustment.team).to_string(),
+                points: adjustment.points,
+                reason: adjustment.reason.clone(),
+                matchday: adjustment.matchday,
+            })
+            .collect()
+    }
+
+    // registers `team` as declared, with an empty record
+    fn enroll(&mut self, team: &str) {
+        let team = self.teams.intern(team);
+        if !self.roster.contains(&team) {
+            self.roster.push(team);
+            self.records.entry(team).or_default();
+        }
+    }
+
+    // the canonical spelling of `team`, which must be on the roster if there is one
+    fn canonical<'a>(&self, team: &'a str) -> Result<Cow<'a, str>, BookingError> {
+        let unknown = || BookingError::UnknownTeam(team.to_string());
+        let canonical = self.aliases.resolve(team).ok_or_else(unknown)?;
+        if self.roster.is_empty() {
+            return Ok(canonical);
+        }
+        match self.teams.id(&canonical) {
+            Some(id) if self.roster.contains(&id) => Ok(canonical),
+            _ => Err(BookingError::UnknownTeam(canonical.into_owned())),
+        }
+    }
+
+    // the last snapshot of a matchday before `matchday`
+    fn closed_before(&self, matchday: usize) -> Option<&MatchdaySnapshot> {
+        self.history
+            .iter()
+            .rev()
+            .find(|closed| closed.matchday < matchday)
+    }
+
+    // whether the current matchday has been closed by `finish`
+    fn is_closed(&self) -> bool {
+        self.history
+            .last()
+            .is_some_and(|closed| closed.matchday == self.matchday)
+    }
+
+    fn is_withdrawn(&self, team: TeamId) -> bool {
+        self.withdrawn.contains(&team)
+    }
+
+    // remembers whose results against a withdrawn team `game`, credited to
+    // `matchday`, took away
+    fn expunge(&mut self, game: &Game<TeamId>, matchday: usize) {
+        for (team, opponent) in [(game.home, game.away), (game.away, game.home)] {
+            if self.is_withdrawn(opponent) {
+                let earliest = self.expunged.entry((team, opponent)).or_insert(matchday);
+                *earliest = (*earliest).min(matchday);
             }
         }
+    }
 
-        // add both teams to seen teams for current matchday
-        self.tmp_teams_with_games.insert(game.home_name);
-        self.tmp_teams_with_games.insert(game.away_name);
+    // what the table should point out about `team`
+    fn notes(&self, team: TeamId) -> Vec<Note> {
+        let mut notes = Vec::new();
+        if self.awarded.contains(&team) {
+            notes.push(Note::Awarded);
+        }
+        if self.annulled.contains_key(&team) {
+            notes.push(Note::Annulled);
+        }
+        // in the order the teams withdrew
+        for &withdrawn in &self.withdrawn {
+            if self.expunged.contains_key(&(team, withdrawn)) {
+                notes.push(Note::Expunged(self.teams.name(withdrawn).to_string()));
+            }
+        }
+        for adjustment in self.adjustments.iter().filter(|a| a.team == team) {
+            notes.push(Note::Adjusted {
+                points: adjustment.points,
+                reason: adjustment.reason.clone(),
+            });
+        }
+        notes
     }
 
-    fn add_points_to_team(&mut self, name: &str, points: u8) {
-        let p = self.teams_with_points.entry(name.to_string()).or_insert(0);
-        *p += points;
+    // the results of every team's last `n` games, oldest first
+    fn form_guide(&self, n: usize) -> HashMap<TeamId, Vec<MatchResult>> {
+        let mut form: HashMap<TeamId, Vec<MatchResult>> = HashMap::new();
+        if n == 0 {
+            return form;
+        }
+        for booked in self.by_matchday().into_iter().rev() {
+            let game = &booked.game;
+            let sides = [
+                (game.home, game.home_result()),
+                (game.away, game.home_result().reverse()),
+            ];
+            for (team, result) in sides {
+                let results = form.entry(team).or_default();
+                if results.len() < n {
+                    results.push(result.into());
+                }
+            }
+        }
+        for results in form.values_mut() {
+            results.reverse();
+        }
+        form
+    }
+
+    // the booked games by matchday, in the order of ingestion within one
+    fn by_matchday(&self) -> Vec<&Booked> {
+        let mut games: Vec<&Booked> = self.games.iter().collect();
+        // stable and cheap, as games are mostly ingested in matchday order
+        games.sort_by_key(|booked| booked.matchday);
+        games
+    }
+
+    // every booked game, in the order of ingestion
+    pub(crate) fn games(&self) -> impl Iterator<Item = &Game<TeamId>> {
+        self.games.iter().map(|booked| &booked.game)
+    }
+
+    // `game` with its teams' names, for presentation
+    fn named(&self, game: &Game<TeamId>) -> Game {
+        game.clone()
+            .map_teams(|team| self.teams.name(team).to_string())
+    }
+
+    // recomputes the closed matchdays from `matchday` on after a late result
+    fn revise(&mut self, matchday: usize) -> Result<(), OverflowError> {
+        let revised = self
+            .history
+            .iter()
+            .enumerate()
+            .filter(|(_, closed)| closed.matchday >= matchday)
+            .map(|(idx, closed)| Ok((idx, self.snapshot_at(closed.matchday)?)))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (idx, mut snapshot) in revised {
+            // compare with the revised snapshot of the matchday before
+            snapshot.compare_with(self.closed_before(snapshot.matchday));
+            self.observers
+                .notify(&Event::MatchdayRevised(snapshot.clone()));
+            self.history[idx] = snapshot;
+        }
+        Ok(())
+    }
+
+    // rebuilds all records from the booked games, after some were removed
+    fn recount(&mut self) -> Result<(), OverflowError> {
+        self.records.clear();
+        self.awarded.clear();
+        for &team in &self.roster {
+            if !self.withdrawn.contains(&team) {
+                self.records.insert(team, TeamRecord::default());
+            }
+        }
+        for idx in 0..self.games.len() {
+            let (home, away) = self.credited(
+                &self.games[idx].game,
+                |&team| Some(team),
+                |&team| self.teams.name(team).to_string(),
+            )?;
+            let game = &self.games[idx].game;
+            self.records.insert(game.home, home);
+            self.records.insert(game.away, away);
+            if game.is_awarded() {
+                self.awarded.extend([game.home, game.away]);
+            }
+        }
+        for adjustment in self.adjustments.clone() {
+            if !self.is_withdrawn(adjustment.team) {
+                self.apply(&adjustment)?;
+            }
+        }
+        Ok(())
+    }
+
+    // the records of both teams with the result of `game` booked
+    // `id` tells the id of a team that has one, `name` names it if it overflows
+    fn credited<T, I, N>(
+        &self,
+        game: &Game<T>,
+        id: I,
+        name: N,
+    ) -> Result<(TeamRecord, TeamRecord), OverflowError>
+    where
+        I: Fn(&T) -> Option<TeamId>,
+        N: Fn(&T) -> String,
+    {
+        let (home_points, away_points) = self.points(game);
+        let record = |team: &T| {
+            id(team)
+                .and_then(|team| self.records.get(&team).cloned())
+                .unwrap_or_default()
+        };
+        let overflow = |team: &T| OverflowError { team: name(team) };
+        // losers are booked as well, important if printing of rankings cannot be filled by teams who have earned wins
+        let home = record(&game.home)
+            .with_game(
+                game.home_score,
+                game.away_score,
+                game.home_result(),
+                home_points,
+                false,
+            )
+            .ok_or_else(|| overflow(&game.home))?;
+        let away = record(&game.away)
+            .with_game(
+                game.away_score,
+                game.home_score,
+                game.home_result().reverse(),
+                away_points,
+                true,
+            )
+            .ok_or_else(|| overflow(&game.away))?;
+        Ok((home, away))
+    }
+
+    fn apply(&mut self, adjustment: &Adjustment<TeamId>) -> Result<(), OverflowError> {
+        let record = self.record_mut(adjustment.team);
+        match record.points.checked_add(adjustment.points) {
+            Some(points) => {
+                record.points = points;
+                Ok(())
+            }
+            None => Err(OverflowError {
+                team: self.teams.name(adjustment.team).to_string(),
+            }),
+        }
+    }
+
+    // takes the snapshot of the current matchday and tells the observers about it
+    fn close_matchday(&mut self) -> MatchdaySnapshot {
+        let snapshot = self.snapshot();
+        self.history.push(snapshot.clone());
+        self.observers
+            .notify(&Event::MatchdayClosed(snapshot.clone()));
+        snapshot
+    }
+
+    // points for home and away team
+    fn points<T>(&self, game: &Game<T>) -> (i64, i64) {
+        self.scoring.points(game)
+    }
+
+    fn record_mut(&mut self, team: TeamId) -> &mut TeamRecord {
+        self.records.entry(team).or_default()
     }
 }
 
-fn pluralize<'a>(n: u8) -> &'a str {
-    match n {
-        1 => "",
-        _ => "s",
+// fixtures shared by the tests of several modules
+#[cfg(test)]
+mod testing {
+    use crate::Standings;
+
+    pub(crate) const SAMPLE_INPUT: &str = include_str!("../sample-input.txt");
+
+    // the standings after the first `games` games of the sample input
+    pub(crate) fn sample_standings_after(games: usize) -> Standings {
+        let mut standings = Standings::default();
+        for line in SAMPLE_INPUT.lines().take(games) {
+            standings.ingest(line.parse().unwrap()).unwrap();
+        }
+        standings
+    }
+
+    pub(crate) fn sample_standings() -> Standings {
+        sample_standings_after(usize::MAX)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{sample_standings, sample_standings_after, SAMPLE_INPUT};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::str::FromStr;
 
     #[test]
-    fn game_from_str_works() {
-        let line = "San Jose Earthquakes 3, Santa Cruz Slugs 3";
-        let game = Game::from_str(line).unwrap();
-        assert_eq!(game.home_name, "San Jose Earthquakes");
-        assert_eq!(game.away_name, "Santa Cruz Slugs");
-        assert_eq!(game.home_score, 3);
-        assert_eq!(game.away_score, 3);
+    fn standings_ingest_works() {
+        let mut standings = Standings::default();
+        assert_eq!(standings.records.len(), 0);
+        standings
+            .ingest(Game::from_str("San Jose Earthquakes 3, Santa Cruz Slugs 3").unwrap())
+            .unwrap();
+        assert_eq!(standings.matchday, 1);
+        assert_eq!(standings.records.len(), 2);
+        standings
+            .ingest(Game::from_str("Capitola Seahorses 1, Aptos FC 0").unwrap())
+            .unwrap();
+        standings
+            .ingest(Game::from_str("Felton Lumberjacks 2, Monterey United 0").unwrap())
+            .unwrap();
+        standings
+            .ingest(Game::from_str("Felton Lumberjacks 1, Aptos FC 2").unwrap())
+            .unwrap();
+        standings
+            .ingest(Game::from_str("Santa Cruz Slugs 0, Capitola Seahorses 0").unwrap())
+            .unwrap();
+        standings
+            .ingest(Game::from_str("Monterey United 4, San Jose Earthquakes 2").unwrap())
+            .unwrap();
+        standings
+            .ingest(Game::from_str("Santa Cruz Slugs 2, Aptos FC 3").unwrap())
+            .unwrap();
+        standings
+            .ingest(Game::from_str("San Jose Earthquakes 1, Felton Lumberjacks 4").unwrap())
+            .unwrap();
+        standings
+            .ingest(Game::from_str("Monterey United 1, Capitola Seahorses 0").unwrap())
+            .unwrap();
+        standings
+            .ingest(Game::from_str("Aptos FC 2, Monterey United 0").unwrap())
+            .unwrap();
+        standings
+            .ingest(Game::from_str("Capitola Seahorses 5, San Jose Earthquakes 5").unwrap())
+            .unwrap();
+        standings
+            .ingest(Game::from_str("Santa Cruz Slugs 1, Felton Lumberjacks 1").unwrap())
+            .unwrap();
+        assert_eq!(standings.matchday, 4);
+        assert_eq!(standings.records.len(), 6);
+        assert_eq!(standings.record("Aptos FC").map(|r| r.points), Some(9));
+        assert_eq!(
+            standings.record("Felton Lumberjacks").map(|r| r.points),
+            Some(7)
+        );
+        assert_eq!(
+            standings.record("Monterey United").map(|r| r.points),
+            Some(6)
+        );
+        assert_eq!(standings.record("FC St. Pauli"), None);
     }
 
     #[test]
-    fn outcome_draw_works() {
-        let line = "San Jose Earthquakes 3, Santa Cruz Slugs 3";
-        let game = Game::from_str(line).unwrap();
+    fn standings_keep_full_record() {
+        let standings = sample_standings();
+        assert_eq!(
+            standings.record("Aptos FC"),
+            Some(&TeamRecord {
+                played: 4,
+                won: 3,
+                drawn: 0,
+                lost: 1,
+                goals_for: 7,
+                goals_against: 4,
+                away_goals: 5,
+                points: 9,
+            })
+        );
         assert_eq!(
-            game.outcome(),
-            Outcome::DRAW(("San Jose Earthquakes", "Santa Cruz Slugs"))
+            standings
+                .record("San Jose Earthquakes")
+                .unwrap()
+                .goal_difference(),
+            -5
         );
     }
 
     #[test]
-    fn outcome_home_win_works() {
-        let line = "Capitola Seahorses 1, Aptos FC 0";
-        let game = Game::from_str(line).unwrap();
+    fn write_rankings_lists_the_top_teams() {
+        let mut standings = Standings::new(3, 1, 1);
+        standings
+            .ingest("Aptos FC 2, Monterey United 0".parse().unwrap())
+            .unwrap();
+        let mut out = Vec::new();
+        standings.write_rankings(&mut out).unwrap();
         assert_eq!(
-            game.outcome(),
-            Outcome::WINLOSS(("Capitola Seahorses", "Aptos FC"))
+            String::from_utf8(out).unwrap(),
+            "Matchday 1\nAptos FC, 3 pts\n"
         );
     }
 
     #[test]
-    fn outcome_away_win_works() {
-        let line = "San Jose Earthquakes 1, Felton Lumberjacks 4";
-        let game = Game::from_str(line).unwrap();
+    fn rankings_share_positions_of_tied_teams() {
+        let mut standings = sample_standings_after(9);
+        let positions = |standings: &Standings| -> Vec<usize> {
+            standings
+                .rankings()
+                .iter()
+                .map(|entry| entry.position)
+                .collect()
+        };
+        assert_eq!(positions(&standings), vec![1, 1, 1, 4, 5, 6]);
+        standings.set_tiebreakers(vec![Tiebreaker::GoalDifference]);
+        assert_eq!(positions(&standings), vec![1, 2, 2, 4, 5, 6]);
+    }
+
+    #[test]
+    fn explicit_matchdays_override_inference() {
+        let mut standings = Standings::default();
+        assert_eq!(standings.start_matchday(1), Ok(None));
+        standings
+            .ingest("Aptos FC 1, Monterey United 0".parse().unwrap())
+            .unwrap();
+        // Aptos FC plays twice, without markers this would start matchday 2
         assert_eq!(
-            game.outcome(),
-            Outcome::WINLOSS(("Felton Lumberjacks", "San Jose Earthquakes"))
+            standings.ingest("Felton Lumberjacks 0, Aptos FC 2".parse().unwrap()),
+            Ok(None)
         );
+        let closed = standings.start_matchday(2).unwrap().unwrap();
+        assert_eq!(closed.matchday, 1);
+        assert_eq!(closed.entry("Aptos FC").unwrap().record.points, 6);
+        standings
+            .ingest("Monterey United 1, Felton Lumberjacks 1".parse().unwrap())
+            .unwrap();
+        assert_eq!(standings.finish().map(|s| s.matchday), Some(2));
+        assert_eq!(standings.finish(), None);
     }
 
     #[test]
-    fn standings_ingest_works() {
+    fn games_after_finish_start_the_next_matchday() {
         let mut standings = Standings::default();
-        assert_eq!(standings.teams_with_points.len(), 0);
-        standings.ingest(Game::from_str("San Jose Earthquakes 3, Santa Cruz Slugs 3").unwrap());
-        assert_eq!(standings.matchday, 1);
-        assert_eq!(standings.teams_with_points.len(), 2);
-        standings.ingest(Game::from_str("Capitola Seahorses 1, Aptos FC 0").unwrap());
-        standings.ingest(Game::from_str("Felton Lumberjacks 2, Monterey United 0").unwrap());
-        standings.ingest(Game::from_str("Felton Lumberjacks 1, Aptos FC 2").unwrap());
-        standings.ingest(Game::from_str("Santa Cruz Slugs 0, Capitola Seahorses 0").unwrap());
-        standings.ingest(Game::from_str("Monterey United 4, San Jose Earthquakes 2").unwrap());
-        standings.ingest(Game::from_str("Santa Cruz Slugs 2, Aptos FC 3").unwrap());
-        standings.ingest(Game::from_str("San Jose Earthquakes 1, Felton Lumberjacks 4").unwrap());
-        standings.ingest(Game::from_str("Monterey United 1, Capitola Seahorses 0").unwrap());
-        standings.ingest(Game::from_str("Aptos FC 2, Monterey United 0").unwrap());
-        standings.ingest(Game::from_str("Capitola Seahorses 5, San Jose Earthquakes 5").unwrap());
-        standings.ingest(Game::from_str("Santa Cruz Slugs 1, Felton Lumberjacks 1").unwrap());
+        standings
+            .ingest("Aptos FC 2, Monterey United 0".parse().unwrap())
+            .unwrap();
+        assert_eq!(standings.finish().map(|s| s.matchday), Some(1));
+        standings
+            .ingest("Monterey United 1, Aptos FC 1".parse().unwrap())
+            .unwrap();
+        assert_eq!(standings.finish().map(|s| s.matchday), Some(2));
+        let matchdays: Vec<usize> = standings.history().iter().map(|s| s.matchday).collect();
+        assert_eq!(matchdays, [1, 2]);
+
+        let mut standings = sample_standings();
+        standings.finish();
+        ingest::ingest_str(SAMPLE_INPUT, &mut standings, ingest::Mode::Strict).unwrap();
+        let matchdays: Vec<usize> = standings.history().iter().map(|s| s.matchday).collect();
+        assert_eq!(matchdays, [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn matchdays_must_start_in_order() {
+        let mut standings = Standings::default();
+        standings.start_matchday(5).unwrap();
+        standings
+            .ingest("Aptos FC 1, Monterey United 0".parse().unwrap())
+            .unwrap();
+        assert_eq!(
+            standings.start_matchday(3),
+            Err(BookingError::MatchdayOutOfOrder(3))
+        );
+        assert_eq!(
+            standings.start_matchday(5),
+            Err(BookingError::MatchdayOutOfOrder(5))
+        );
+        assert!(standings.start_matchday(6).unwrap().is_some());
+        // no game yet on matchday 6, so the marker may still be corrected
+        assert_eq!(standings.start_matchday(7), Ok(None));
+        assert_eq!(
+            standings.start_matchday(5),
+            Err(BookingError::MatchdayOutOfOrder(5))
+        );
+        standings
+            .ingest("Aptos FC 1, Felton Lumberjacks 0".parse().unwrap())
+            .unwrap();
+        standings.finish();
+        let matchdays: Vec<usize> = standings.history().iter().map(|s| s.matchday).collect();
+        assert_eq!(matchdays, vec![5, 7]);
+    }
+
+    #[test]
+    fn late_games_are_credited_to_their_matchday() {
+        let revised = Rc::new(RefCell::new(Vec::new()));
+        let seen = Rc::clone(&revised);
+        let mut standings = Standings::default();
+        standings.add_observer(move |event: &Event| {
+            if let Event::MatchdayRevised(snapshot) = event {
+                seen.borrow_mut().push(snapshot.clone());
+            }
+        });
+        let lines = SAMPLE_INPUT.lines();
+        // "Aptos FC 2, Monterey United 0" from matchday 3 is postponed
+        for line in lines.filter(|line| !line.starts_with("Aptos FC 2")) {
+            standings.ingest(line.parse().unwrap()).unwrap();
+        }
+        assert_eq!(standings.matchday, 4);
+        assert_eq!(
+            standings
+                .snapshot_at(3)
+                .unwrap()
+                .entry("Aptos FC")
+                .unwrap()
+                .record
+                .points,
+            6
+        );
+
+        let late: Game = "Aptos FC 2, Monterey United 0".parse().unwrap();
+        assert_eq!(standings.ingest(late.clone().with_matchday(3)), Ok(None));
         assert_eq!(standings.matchday, 4);
-        assert_eq!(standings.teams_with_points.len(), 6);
-        assert_eq!(standings.teams_with_points.get("Aptos FC"), Some(&9));
+        assert_eq!(standings.record("Aptos FC").unwrap().points, 9);
+
+        let revised = revised.borrow();
+        assert_eq!(revised.len(), 1);
+        assert_eq!(revised[0].matchday, 3);
+        assert_eq!(revised[0].late_games, vec![late.clone().with_matchday(3)]);
+        assert_eq!(revised[0].entries[0].team, "Aptos FC");
+        assert_eq!(revised[0].entries[0].record.points, 9);
+        assert_eq!(standings.snapshot_at(2).unwrap().late_games, vec![]);
+
+        assert_eq!(
+            standings.ingest(late.with_matchday(5)),
+            Err(BookingError::FutureMatchday(5))
+        );
+        assert_eq!(standings.record("Aptos FC").unwrap().played, 4);
+    }
+
+    #[test]
+    fn annul_and_withdraw_remove_results() {
+        let mut standings = sample_standings();
+        assert_eq!(standings.annul("Aptos FC", "Monterey United"), Ok(true));
+        assert_eq!(standings.annul("Aptos FC", "Monterey United"), Ok(false));
+        assert_eq!(standings.record("Aptos FC").unwrap().points, 6);
+        assert_eq!(standings.record("Aptos FC").unwrap().played, 3);
+        let aptos = standings.snapshot();
+        assert_eq!(aptos.entry("Aptos FC").unwrap().notes, vec![Note::Annulled]);
+
+        // Felton beat Monterey United and San Jose, drew with Santa Cruz
+        standings.withdraw("Felton Lumberjacks").unwrap();
+        assert_eq!(standings.record("Felton Lumberjacks"), None);
+        assert_eq!(standings.record("Aptos FC").unwrap().points, 3);
+        assert_eq!(standings.record("Santa Cruz Slugs").unwrap().points, 2);
+        let snapshot = standings.snapshot();
         assert_eq!(
-            standings.teams_with_points.get("Felton Lumberjacks"),
-            Some(&7)
+            snapshot.entry("Aptos FC").unwrap().notes,
+            vec![
+                Note::Annulled,
+                Note::Expunged("Felton Lumberjacks".to_string())
+            ]
         );
-        assert_eq!(standings.teams_with_points.get("Monterey United"), Some(&6));
-        assert_eq!(standings.teams_with_points.get("FC St. Pauli"), None);
+        assert!(snapshot
+            .entry("Capitola Seahorses")
+            .unwrap()
+            .notes
+            .is_empty());
+
+        standings
+            .ingest("Felton Lumberjacks 5, Aptos FC 0".parse().unwrap())
+            .unwrap();
+        assert_eq!(standings.record("Felton Lumberjacks"), None);
+        assert_eq!(standings.record("Aptos FC").unwrap().played, 2);
+    }
+
+    #[test]
+    fn past_snapshots_only_note_what_happened_to_their_games() {
+        let mut standings = sample_standings();
+        // played on matchday 4
+        standings.annul("Aptos FC", "Monterey United").unwrap();
+        standings
+            .ingest(
+                "Capitola Seahorses 1, Felton Lumberjacks 1 (matchday 1)"
+                    .parse()
+                    .unwrap(),
+            )
+            .unwrap();
+        for revised in standings.history() {
+            assert!(revised.entry("Aptos FC").unwrap().notes.is_empty());
+        }
+        let notes = |standings: &Standings, matchday: usize| {
+            let snapshot = standings.snapshot_at(matchday).unwrap();
+            snapshot.entry("Aptos FC").unwrap().notes.clone()
+        };
+        assert_eq!(notes(&standings, 4), vec![Note::Annulled]);
+
+        // Aptos FC played Felton Lumberjacks on matchday 2
+        standings.withdraw("Felton Lumberjacks").unwrap();
+        assert!(notes(&standings, 1).is_empty());
+        assert_eq!(
+            notes(&standings, 2),
+            vec![Note::Expunged("Felton Lumberjacks".to_string())]
+        );
+    }
+
+    #[test]
+    fn awarded_note_follows_the_booked_games() {
+        let mut standings = sample_standings();
+        standings
+            .ingest(
+                "Aptos FC 3, Felton Lumberjacks 0 (awarded)"
+                    .parse()
+                    .unwrap(),
+            )
+            .unwrap();
+        let notes = |standings: &Standings, team: &str| {
+            standings.snapshot().entry(team).unwrap().notes.clone()
+        };
+        assert_eq!(notes(&standings, "Felton Lumberjacks"), vec![Note::Awarded]);
+        assert!(notes(&standings, "Monterey United").is_empty());
+        standings.annul("Aptos FC", "Felton Lumberjacks").unwrap();
+        assert_eq!(
+            notes(&standings, "Felton Lumberjacks"),
+            vec![Note::Annulled]
+        );
+    }
+
+    #[test]
+    fn adjustments_count_from_their_matchday() {
+        let mut standings = Standings::default();
+        let mut lines = SAMPLE_INPUT.lines();
+        for line in lines.by_ref().take(6) {
+            standings.ingest(line.parse().unwrap()).unwrap();
+        }
+        // matchday 2
+        standings.adjust("Aptos FC", -4, "administration").unwrap();
+        for line in lines {
+            standings.ingest(line.parse().unwrap()).unwrap();
+        }
+        assert_eq!(standings.record("Aptos FC").unwrap().points, 5);
+        assert_eq!(standings.adjustments()[0].matchday, 2);
+        assert_eq!(
+            standings
+                .snapshot_at(1)
+                .unwrap()
+                .entry("Aptos FC")
+                .unwrap()
+                .record
+                .points,
+            0
+        );
+        let matchday_2 = standings.snapshot_at(2).unwrap();
+        let aptos = matchday_2.entry("Aptos FC").unwrap();
+        assert_eq!(aptos.record.points, -1);
+        assert_eq!(
+            aptos.notes,
+            vec![Note::Adjusted {
+                points: -4,
+                reason: "administration".to_string()
+            }]
+        );
+        assert_eq!(matchday_2.entries.last().unwrap().team, "Aptos FC");
+    }
+
+    #[test]
+    fn ingest_reports_overflow_without_booking() {
+        let mut standings = Standings::new(i64::MAX, 1, 3);
+        standings
+            .ingest("Aptos FC 110, Monterey United 98".parse().unwrap())
+            .unwrap();
+        assert_eq!(
+            standings.ingest("Aptos FC 1, Felton Lumberjacks 0".parse().unwrap()),
+            Err(BookingError::Overflow(OverflowError {
+                team: "Aptos FC".to_string()
+            }))
+        );
+        assert_eq!(standings.record("Aptos FC").unwrap().played, 1);
+        assert_eq!(standings.record("Felton Lumberjacks"), None);
+        assert_eq!(standings.teams().id("Felton Lumberjacks"), None);
+        assert_eq!(standings.teams().len(), 2);
+        assert!(standings.adjust("Aptos FC", 1, "").is_err());
+    }
+
+    #[test]
+    fn games_are_booked_with_interned_teams() {
+        let mut standings = sample_standings();
+        assert_eq!(standings.teams().len(), 6);
+        standings
+            .ingest(Game::from_str("Aptos FC 1, Watsonville Wolves 0").unwrap())
+            .unwrap();
+        assert_eq!(standings.teams().len(), 7);
+        let aptos = standings.teams().id("Aptos FC").unwrap();
+        let wolves = standings.teams().id("Watsonville Wolves").unwrap();
+        assert_eq!(standings.teams().name(wolves), "Watsonville Wolves");
+        assert_eq!(
+            standings.games().last().unwrap().outcome(),
+            Outcome::WINLOSS((aptos, wolves))
+        );
+    }
+
+    #[test]
+    fn roster_rejects_unknown_teams() {
+        let mut standings = Standings::default();
+        standings.declare_team("Aptos FC", vec!["Aptos"]).unwrap();
+        standings.declare_team("Monterey United", None).unwrap();
+        assert_eq!(standings.idle_teams(), vec!["Aptos FC", "Monterey United"]);
+        assert_eq!(
+            standings.ingest(Game::from_str("Aptos FC 1, Felton Lumberjacks 0").unwrap()),
+            Err(BookingError::UnknownTeam("Felton Lumberjacks".to_string()))
+        );
+        assert!(standings.withdraw("Felton Lumberjacks").is_err());
+        assert_eq!(standings.teams().id("Felton Lumberjacks"), None);
+        standings
+            .ingest(Game::from_str("Aptos FC 1, Monterey United 1").unwrap())
+            .unwrap();
+        assert!(standings.idle_teams().is_empty());
+        assert_eq!(standings.roster().count(), 2);
+    }
+
+    #[test]
+    fn declared_teams_and_alias_tables_are_merged() {
+        let mut standings = Standings::default();
+        standings.declare_team("Aptos FC", vec!["Aptos"]).unwrap();
+        standings
+            .set_aliases("Monterey United = Monterey".parse().unwrap())
+            .unwrap();
+        let aliases = standings.aliases();
+        assert_eq!(aliases.resolve("Aptos").as_deref(), Some("Aptos FC"));
+        assert_eq!(
+            aliases.resolve("Monterey").as_deref(),
+            Some("Monterey United")
+        );
+        assert!(aliases.is_strict());
+        assert_eq!(standings.roster().collect::<Vec<_>>(), vec!["Aptos FC"]);
+        assert!(standings
+            .set_aliases("Aptos United = Aptos".parse().unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn declared_aliases_apply_to_games_and_commands() {
+        let mut standings = Standings::default();
+        standings.declare_team("Aptos FC", vec!["Aptos"]).unwrap();
+        standings.declare_team("Monterey United", vec![]).unwrap();
+        standings
+            .ingest(Game::from_str("Aptos 1, Monterey United 0").unwrap())
+            .unwrap();
+        standings
+            .ingest(Game::from_str("Monterey United 2, Aptos  FC 2").unwrap())
+            .unwrap();
+        assert_eq!(standings.record("Aptos FC").unwrap().points, 4);
+        standings
+            .adjust("Aptos", -1, "fielding an ineligible player")
+            .unwrap();
+        assert_eq!(standings.record("Aptos FC").unwrap().points, 3);
+        assert_eq!(standings.annul("Aptos", "Monterey United"), Ok(true));
+        standings.withdraw("Aptos").unwrap();
+        assert_eq!(standings.record("Aptos FC"), None);
+        assert_eq!(standings.teams().len(), 2);
+        assert_eq!(
+            standings.withdraw("Felton"),
+            Err(BookingError::UnknownTeam("Felton".to_string()))
+        );
+    }
+
+    #[test]
+    fn log_keeps_fixtures_per_team() {
+        let mut standings = sample_standings();
+        let late = Game::from_str("Aptos FC 0, Felton Lumberjacks 1 (matchday 1)").unwrap();
+        standings.ingest(late).unwrap();
+        let log: Vec<LoggedGame> = standings.log().collect();
+        assert_eq!(log.len(), 13);
+        assert_eq!(log[12].position, 12);
+        assert_eq!((log[12].matchday, log[12].played), (1, 4));
+
+        let fixtures = standings.fixtures("Aptos FC");
+        let on_matchday_1: Vec<&str> = fixtures
+            .iter()
+            .filter(|fixture| fixture.matchday == 1)
+            .map(Fixture::opponent)
+            .collect();
+        assert_eq!(
+            on_matchday_1,
+            vec!["Capitola Seahorses", "Felton Lumberjacks"]
+        );
+        assert_eq!(fixtures[1].position, 12);
+        assert!(fixtures[1].is_home());
+        assert_eq!((fixtures[1].scored(), fixtures[1].conceded()), (0, 1));
+        assert_eq!(fixtures[1].points, 0);
+        assert_eq!(
+            standings.results("Aptos FC"),
+            fixtures.iter().map(Fixture::result).collect::<Vec<_>>()
+        );
+        assert_eq!(standings.results("Aptos FC")[1], MatchResult::Loss);
+        assert!(standings.fixtures("Watsonville Wolves").is_empty());
+    }
+
+    #[test]
+    fn history_keeps_every_matchday() {
+        let mut standings = sample_standings();
+        standings.finish();
+        assert_eq!(
+            standings
+                .history()
+                .iter()
+                .map(|snapshot| snapshot.matchday)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        let timeline = standings.timeline("Felton Lumberjacks");
+        assert_eq!(timeline.len(), 4);
+        let first_top = timeline.iter().find(|entry| entry.position == 1).unwrap();
+        assert_eq!((first_top.matchday, first_top.points), (1, 3));
+
+        // a late game replaces the snapshots of the matchdays it changes
+        let late = Game::from_str("Aptos FC 9, Felton Lumberjacks 0 (matchday 3)").unwrap();
+        standings.ingest(late).unwrap();
+        let history = standings.history();
+        assert!(!history[1].is_revised());
+        assert!(history[2].is_revised());
+        assert_eq!(history[3].entries[0].team, "Aptos FC");
+        assert_eq!(standings.timeline("Aptos FC")[2].position, 1);
+    }
+
+    #[test]
+    fn form_lists_the_latest_results() {
+        let mut standings = sample_standings();
+        standings.set_form_length(3);
+        let entry = standings
+            .rankings()
+            .into_iter()
+            .find(|entry| entry.team == "Capitola Seahorses")
+            .unwrap();
+        let form: String = entry.form.iter().map(ToString::to_string).collect();
+        assert_eq!(form, "DLD");
+
+        let recent = standings.form_table(1).unwrap();
+        let aptos = recent.entry("Aptos FC").unwrap();
+        assert_eq!((aptos.record.played, aptos.record.points), (1, 3));
+        assert_eq!(aptos.form, vec![MatchResult::Win]);
+        assert_eq!(recent.entries.len(), 6);
     }
 }
@@ -1,25 +1,182 @@
-use league_rankings::{Game, Standings};
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
+use league_rankings::ingest::{self, Mode};
+use league_rankings::{
+    Aliases, Event, PositionStyle, Printer, ScoringRules, Standings, Tiebreaker,
+};
+use std::fs::{self, File};
+use std::io::{self, BufReader, Write};
+use std::process;
+
+// exit codes besides 0 for a clean run
+const EXIT_FAILURE: i32 = 1; // input could not be read or was rejected in strict mode
+const EXIT_SKIPPED: i32 = 2; // lenient mode skipped at least one line
+
+const USAGE: &str =
+    "[--strict|--lenient] [--table] [--tiebreakers gd,gf,...|uefa] [--reapply-h2h] [--positions|--mark-shared] [--include-ties] [--movement] [--scoring nhl|iihf|win=2,...] [--scoring-file path] [--aliases path|--roster path] [--fold-case] [--timeline] [--form n] [--stats] filename";
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 {
-        panic!("please specify input file: {} filename", args[0]);
+    let mut mode = Mode::Strict;
+    let mut full_table = false;
+    let mut timeline = false;
+    let mut form = 0;
+    let mut stats = false;
+    let mut tiebreakers = Vec::new();
+    let mut reapply_h2h = false;
+    let mut scoring = ScoringRules::default();
+    let mut aliases = Aliases::new();
+    let mut fold_case = false;
+    let mut printer = Printer::default();
+    let mut files = Vec::new();
+    let mut iter = args[1..].iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--strict" => mode = Mode::Strict,
+            "--lenient" => mode = Mode::Lenient,
+            "--table" => full_table = true,
+            "--timeline" => timeline = true,
+            "--stats" => stats = true,
+            "--form" => {
+                form = iter
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or_else(|| panic!("usage: {} {}", args[0], USAGE));
+                printer.show_form = true;
+            }
+            "--reapply-h2h" => reapply_h2h = true,
+            "--positions" => printer.position_style = PositionStyle::Numbered,
+            "--mark-shared" => printer.position_style = PositionStyle::MarkShared,
+            "--include-ties" => printer.include_ties = true,
+            "--movement" => printer.show_movement = true,
+            "--tiebreakers" => {
+                let list = iter
+                    .next()
+                    .unwrap_or_else(|| panic!("usage: {} {}", args[0], USAGE));
+                if list == "uefa" {
+                    tiebreakers.extend_from_slice(Tiebreaker::UEFA);
+                    reapply_h2h = true;
+                    continue;
+                }
+                for name in list.split(',') {
+                    let tiebreaker: Tiebreaker =
+                        name.trim().parse().unwrap_or_else(|err| panic!("{}", err));
+                    tiebreakers.push(tiebreaker);
+                }
+            }
+            "--scoring" | "--scoring-file" => {
+                let value = iter
+                    .next()
+                    .unwrap_or_else(|| panic!("usage: {} {}", args[0], USAGE));
+                let spec = if arg == "--scoring-file" {
+                    fs::read_to_string(value).expect("Cannot read scoring file")
+                } else {
+                    value.to_string()
+                };
+                scoring = spec.parse().unwrap_or_else(|err| panic!("{}", err));
+            }
+            "--aliases" | "--roster" => {
+                let path = iter
+                    .next()
+                    .unwrap_or_else(|| panic!("usage: {} {}", args[0], USAGE));
+                let table = fs::read_to_string(path).expect("Cannot read alias file");
+                aliases = table.parse().unwrap_or_else(|err| panic!("{}", err));
+                // a roster rejects every team it does not list
+                aliases.set_strict(arg == "--roster");
+            }
+            "--fold-case" => fold_case = true,
+            _ => files.push(arg),
+        }
+    }
+    if files.len() != 1 {
+        panic!("please specify input file: {} {}", args[0], USAGE);
     }
 
-    let filename = &args[1];
+    let filename = files[0];
 
     // open fs stream
     let f = File::open(filename).expect("Cannot open file");
     let f = BufReader::new(f);
 
     let mut standings = Standings::default();
+    standings.set_tiebreakers(tiebreakers);
+    standings.set_head_to_head_reapplied(reapply_h2h);
+    standings.set_scoring(scoring);
+    aliases.set_case_insensitive(fold_case);
+    standings
+        .set_aliases(aliases)
+        .unwrap_or_else(|err| panic!("{}", err));
+    standings.set_form_length(form);
+
+    let report_printer = printer.clone();
+    let mut first = true;
+    standings.add_observer(move |event: &Event| {
+        let snapshot = match event {
+            Event::MatchdayClosed(snapshot) | Event::MatchdayRevised(snapshot) => snapshot,
+            _ => return,
+        };
+        {
+            let stdout = io::stdout();
+            let mut out = stdout.lock();
+            // separator between matchdays, but not at the end of program
+            if !first {
+                writeln!(out).expect("Cannot write to stdout");
+            }
+            first = false;
+            if full_table {
+                printer.write_table(&mut out, snapshot)
+            } else {
+                printer.write_rankings(&mut out, snapshot)
+            }
+            .expect("Cannot write to stdout");
+        }
+    });
+
+    let report = match ingest::ingest(f, &mut standings, mode) {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("{}: {}", filename, err);
+            process::exit(EXIT_FAILURE);
+        }
+    };
+
+    if timeline && !standings.history().is_empty() {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        writeln!(out).expect("Cannot write to stdout");
+        report_printer
+            .write_timeline(&mut out, standings.history())
+            .expect("Cannot write to stdout");
+    }
+
+    if form > 0 && !standings.history().is_empty() {
+        let table = match standings.form_table(form) {
+            Ok(table) => table,
+            Err(err) => {
+                eprintln!("{}: {}", filename, err);
+                process::exit(EXIT_FAILURE);
+            }
+        };
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        writeln!(out).expect("Cannot write to stdout");
+        report_printer
+            .write_form_table(&mut out, &table, form)
+            .expect("Cannot write to stdout");
+    }
 
-    for line in f.lines() {
-        // lazy reading into buffer and ingesting lines one by one
-        standings.ingest(Game::from_str(&line.unwrap()).unwrap());
+    if stats && !standings.history().is_empty() {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        writeln!(out).expect("Cannot write to stdout");
+        report_printer
+            .write_stats(&mut out, &standings.stats())
+            .expect("Cannot write to stdout");
+    }
+
+    // teams declared but never seen only warrant a warning
+    if !report.is_clean() || !report.idle_teams.is_empty() {
+        eprintln!("{}: {}", filename, report);
+    }
+    if !report.is_clean() {
+        process::exit(EXIT_SKIPPED);
     }
-    standings.print_rankings();
 }
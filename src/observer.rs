@@ -0,0 +1,50 @@
+use crate::MatchdaySnapshot;
+use std::fmt;
+
+/// Something that happened in `Standings` that observers may want to act on.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Event {
+    /// A matchday is complete, either because a game of the next one was
+    /// ingested or because `Standings::finish` was called.
+    MatchdayClosed(MatchdaySnapshot),
+    /// A matchday that was already closed changed because a game credited to
+    /// it, or to a matchday before, was played late. The snapshot lists these
+    /// games in `late_games`.
+    MatchdayRevised(MatchdaySnapshot),
+}
+
+/// Receives events from `Standings`, see `Standings::add_observer`.
+///
+/// Implemented for all `FnMut(&Event)` closures.
+pub trait Observer {
+    fn notify(&mut self, event: &Event);
+}
+
+impl<F: FnMut(&Event)> Observer for F {
+    fn notify(&mut self, event: &Event) {
+        self(event)
+    }
+}
+
+// registered observers, in a wrapper so `Standings` can keep deriving `Debug`
+#[derive(Default)]
+pub(crate) struct Observers(Vec<Box<dyn Observer>>);
+
+impl Observers {
+    pub(crate) fn push(&mut self, observer: Box<dyn Observer>) {
+        self.0.push(observer);
+    }
+
+    pub(crate) fn notify(&mut self, event: &Event) {
+        for observer in &mut self.0 {
+            observer.notify(event);
+        }
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
@@ -0,0 +1,119 @@
+use std::cmp::Ordering;
+
+/// A team's line in the league table.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TeamRecord {
+    pub played: u32,
+    pub won: u32,
+    pub drawn: u32,
+    pub lost: u32,
+    pub goals_for: u64,
+    pub goals_against: u64,
+    pub away_goals: u64, // goals scored in away games, part of `goals_for`
+    pub points: i64,
+}
+
+impl TeamRecord {
+    pub fn goal_difference(&self) -> i64 {
+        // saturates only beyond any realistic number of goals
+        let difference = i128::from(self.goals_for) - i128::from(self.goals_against);
+        difference.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+    }
+
+    // books a single game from this team's point of view, `result` tells
+    // whether it was won, which for a shootout is not decided by the score.
+    // Returns the updated record, or `None` if a total would overflow.
+    pub(crate) fn with_game(
+        &self,
+        scored: u32,
+        conceded: u32,
+        result: Ordering,
+        points: i64,
+        away: bool,
+    ) -> Option<TeamRecord> {
+        let mut next = self.clone();
+        next.played = next.played.checked_add(1)?;
+        match result {
+            Ordering::Greater => next.won += 1,
+            Ordering::Less => next.lost += 1,
+            Ordering::Equal => next.drawn += 1,
+        }
+        next.goals_for = next.goals_for.checked_add(u64::from(scored))?;
+        next.goals_against = next.goals_against.checked_add(u64::from(conceded))?;
+        if away {
+            next.away_goals = next.away_goals.checked_add(u64::from(scored))?;
+        }
+        next.points = next.points.checked_add(points)?;
+        Some(next)
+    }
+
+    // like `with_game`, but the totals saturate at their bounds, for records
+    // that only serve as sort keys such as head-to-head mini-tables
+    pub(crate) fn with_game_saturating(
+        &self,
+        scored: u32,
+        conceded: u32,
+        result: Ordering,
+        points: i64,
+        away: bool,
+    ) -> TeamRecord {
+        let mut next = self.clone();
+        next.played = next.played.saturating_add(1);
+        match result {
+            Ordering::Greater => next.won = next.won.saturating_add(1),
+            Ordering::Less => next.lost = next.lost.saturating_add(1),
+            Ordering::Equal => next.drawn = next.drawn.saturating_add(1),
+        }
+        next.goals_for = next.goals_for.saturating_add(u64::from(scored));
+        next.goals_against = next.goals_against.saturating_add(u64::from(conceded));
+        if away {
+            next.away_goals = next.away_goals.saturating_add(u64::from(scored));
+        }
+        next.points = next.points.saturating_add(points);
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_game_books_result_and_goals() {
+        let record = TeamRecord::default()
+            .with_game(2, 0, Ordering::Greater, 3, false)
+            .and_then(|r| r.with_game(1, 1, Ordering::Equal, 1, true))
+            .and_then(|r| r.with_game(0, 4, Ordering::Less, 0, true))
+            .unwrap();
+        assert_eq!(
+            record,
+            TeamRecord {
+                played: 3,
+                won: 1,
+                drawn: 1,
+                lost: 1,
+                goals_for: 3,
+                goals_against: 5,
+                away_goals: 1,
+                points: 4,
+            }
+        );
+        assert_eq!(record.goal_difference(), -2);
+    }
+
+    #[test]
+    fn with_game_refuses_to_overflow() {
+        let record = TeamRecord {
+            points: i64::MAX - 1,
+            ..Default::default()
+        };
+        assert_eq!(record.with_game(1, 0, Ordering::Greater, 3, false), None);
+        let record = record
+            .with_game(u32::MAX, 0, Ordering::Greater, 1, false)
+            .unwrap();
+        assert_eq!(record.points, i64::MAX);
+        assert_eq!(record.goals_for, u64::from(u32::MAX));
+        let record = record.with_game_saturating(1, 0, Ordering::Greater, 3, false);
+        assert_eq!((record.points, record.won), (i64::MAX, 2));
+    }
+}
@@ -0,0 +1,327 @@
+use crate::{Decision, Game, ParseScoringError};
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// Points awarded per game, see `Standings::set_scoring`.
+///
+/// A single game's points saturate at the bounds of `i64`; only the totals in
+/// `TeamRecord` are checked for overflow.
+///
+/// Results decided in overtime or by a shootout, and forfeits, have their own
+/// values so that e.g. the NHL's 2-1-0 or the IIHF's 3-2-1-0 systems can be
+/// modelled. Overtime and shootout values left at `None` follow `win` and
+/// `loss`. Bonus points are added on top of the result's points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoringRules {
+    pub win: i64,
+    pub draw: i64,
+    pub loss: i64,
+    pub overtime_win: Option<i64>, // won in overtime or extra time
+    pub overtime_loss: Option<i64>,
+    pub shootout_win: Option<i64>,
+    pub shootout_loss: Option<i64>,
+    pub forfeit_loss: i64, // for the team that forfeited, may be negative
+    pub bonus: Vec<Bonus>,
+}
+
+/// Extra points for a single game, on top of the points for its result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bonus {
+    WinningMargin { margin: u32, points: i64 }, // for winning by `margin` goals or more
+    LosingMargin { margin: u32, points: i64 },  // for losing by `margin` goals or fewer
+    Tries { tries: u32, points: i64 },          // for scoring `tries` tries or more, win or lose
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        ScoringRules::football()
+    }
+}
+
+impl ScoringRules {
+    /// Three points for a win, one for a draw, whatever way the game was won.
+    pub fn football() -> ScoringRules {
+        ScoringRules::win_draw(3, 1)
+    }
+
+    /// `win` points for any win, `draw` points for a draw and none for a loss.
+    pub fn win_draw(win: i64, draw: i64) -> ScoringRules {
+        ScoringRules {
+            win,
+            draw,
+            loss: 0,
+            overtime_win: None,
+            overtime_loss: None,
+            shootout_win: None,
+            shootout_loss: None,
+            forfeit_loss: 0,
+            bonus: Vec::new(),
+        }
+    }
+
+    /// NHL: two points for any win, one for losing in overtime or the shootout.
+    pub fn nhl() -> ScoringRules {
+        ScoringRules {
+            overtime_loss: Some(1),
+            shootout_loss: Some(1),
+            ..ScoringRules::win_draw(2, 1)
+        }
+    }
+
+    /// IIHF: three points for a win in regulation, two for a win in overtime
+    /// or the shootout, one for losing in overtime or the shootout.
+    pub fn iihf() -> ScoringRules {
+        ScoringRules {
+            overtime_win: Some(2),
+            overtime_loss: Some(1),
+            shootout_win: Some(2),
+            shootout_loss: Some(1),
+            ..ScoringRules::win_draw(3, 1)
+        }
+    }
+
+    /// Rugby union: four points for a win, two for a draw, and a bonus point
+    /// each for scoring four or more tries and for losing by seven or fewer.
+    /// Games without tries recorded (see `Game::with_tries`) get no try bonus.
+    pub fn rugby() -> ScoringRules {
+        ScoringRules {
+            bonus: vec![
+                Bonus::Tries {
+                    tries: 4,
+                    points: 1,
+                },
+                Bonus::LosingMargin {
+                    margin: 7,
+                    points: 1,
+                },
+            ],
+            ..ScoringRules::win_draw(4, 2)
+        }
+    }
+
+    /// Points for home and away team.
+    pub fn points<T>(&self, game: &Game<T>) -> (i64, i64) {
+        let (win, loss) = match game.decision() {
+            Decision::Regulation => (None, None),
+            Decision::Overtime => (self.overtime_win, self.overtime_loss),
+            Decision::Shootout | Decision::Penalties { .. } => {
+                (self.shootout_win, self.shootout_loss)
+            }
+        };
+        let (win, loss) = (win.unwrap_or(self.win), loss.unwrap_or(self.loss));
+        let loss = if game.is_awarded() {
+            self.forfeit_loss
+        } else {
+            loss
+        };
+        let (mut home, mut away) = match game.home_result() {
+            Ordering::Greater => (win, loss),
+            Ordering::Less => (loss, win),
+            Ordering::Equal => (self.draw, self.draw),
+        };
+        let tries = game.tries();
+        home = home.saturating_add(self.bonus_points(
+            game.home_score,
+            game.away_score,
+            tries.map(|t| t.0),
+        ));
+        away = away.saturating_add(self.bonus_points(
+            game.away_score,
+            game.home_score,
+            tries.map(|t| t.1),
+        ));
+        (home, away)
+    }
+
+    // bonus points from one team's point of view
+    fn bonus_points(&self, scored: u32, conceded: u32, tries: Option<u32>) -> i64 {
+        self.bonus
+            .iter()
+            .map(|bonus| match *bonus {
+                Bonus::WinningMargin { margin, points }
+                    if scored > conceded && scored - conceded >= margin =>
+                {
+                    points
+                }
+                Bonus::LosingMargin { margin, points }
+                    if scored < conceded && conceded - scored <= margin =>
+                {
+                    points
+                }
+                Bonus::Tries {
+                    tries: needed,
+                    points,
+                } if tries.is_some_and(|tries| tries >= needed) => points,
+                _ => 0,
+            })
+            .fold(0, i64::saturating_add)
+    }
+}
+
+/// Parses a preset name (`football`, `nhl`, `iihf`, `two-points`, `rugby`) and/or
+/// `key=value` overrides, separated by commas or newlines:
+/// `iihf, forfeit=-1` or `win=2, draw=1, losing-margin=7:1`.
+/// Blank items and lines starting with `#` are ignored, so the contents of a
+/// config file can be parsed just the same.
+impl FromStr for ScoringRules {
+    type Err = ParseScoringError;
+
+    fn from_str(s: &str) -> Result<ScoringRules, ParseScoringError> {
+        let mut rules = ScoringRules::football();
+        let items = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .flat_map(|line| line.split(','))
+            .map(str::trim)
+            .filter(|item| !item.is_empty());
+        for item in items {
+            let err = || ParseScoringError(item.to_string());
+            let (key, value) = match item.find('=') {
+                Some(eq) => (item[..eq].trim(), item[eq + 1..].trim()),
+                None => {
+                    rules = match item {
+                        "football" => ScoringRules::football(),
+                        "two-points" => ScoringRules::win_draw(2, 1),
+                        "nhl" => ScoringRules::nhl(),
+                        "iihf" => ScoringRules::iihf(),
+                        "rugby" => ScoringRules::rugby(),
+                        _ => return Err(err()),
+                    };
+                    continue;
+                }
+            };
+            let points = || value.parse::<i64>().map_err(|_| err());
+            match key {
+                "win" => rules.win = points()?,
+                "draw" => rules.draw = points()?,
+                "loss" => rules.loss = points()?,
+                "ot-win" | "overtime-win" => rules.overtime_win = Some(points()?),
+                "ot-loss" | "overtime-loss" => rules.overtime_loss = Some(points()?),
+                "so-win" | "shootout-win" => rules.shootout_win = Some(points()?),
+                "so-loss" | "shootout-loss" => rules.shootout_loss = Some(points()?),
+                "forfeit" => rules.forfeit_loss = points()?,
+                "winning-margin" | "losing-margin" | "try-bonus" => {
+                    // "{threshold}:{points}"
+                    let colon = value.find(':').ok_or_else(err)?;
+                    let threshold = value[..colon].trim().parse().map_err(|_| err())?;
+                    let points = value[colon + 1..].trim().parse().map_err(|_| err())?;
+                    rules.bonus.push(match key {
+                        "winning-margin" => Bonus::WinningMargin {
+                            margin: threshold,
+                            points,
+                        },
+                        "losing-margin" => Bonus::LosingMargin {
+                            margin: threshold,
+                            points,
+                        },
+                        _ => Bonus::Tries {
+                            tries: threshold,
+                            points,
+                        },
+                    });
+                }
+                _ => return Err(err()),
+            }
+        }
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(rules: &ScoringRules, game: &str) -> (i64, i64) {
+        rules.points(&game.parse().unwrap())
+    }
+
+    #[test]
+    fn from_str_reads_presets_and_overrides() {
+        assert_eq!("football".parse(), Ok(ScoringRules::default()));
+        assert_eq!("nhl".parse(), Ok(ScoringRules::nhl()));
+        let rules: ScoringRules = "iihf, forfeit=-1\n# bonus\nlosing-margin=1:1"
+            .parse()
+            .unwrap();
+        assert_eq!(rules.win, 3);
+        assert_eq!(rules.overtime_win, Some(2));
+        assert_eq!(rules.forfeit_loss, -1);
+        assert_eq!(
+            rules.bonus,
+            vec![Bonus::LosingMargin {
+                margin: 1,
+                points: 1
+            }]
+        );
+        assert_eq!(
+            "win=three".parse::<ScoringRules>(),
+            Err(ParseScoringError("win=three".to_string()))
+        );
+        assert_eq!(
+            "hockey".parse::<ScoringRules>(),
+            Err(ParseScoringError("hockey".to_string()))
+        );
+    }
+
+    #[test]
+    fn points_include_loss_points_and_bonus() {
+        let rules: ScoringRules = "win=2, loss=1, winning-margin=3:1, losing-margin=1:1"
+            .parse()
+            .unwrap();
+        assert_eq!(points(&rules, "Ants 4, Bees 1"), (3, 1));
+        assert_eq!(points(&rules, "Ants 1, Bees 2"), (2, 2));
+        assert_eq!(points(&rules, "Ants 0, Bees 0"), (1, 1));
+    }
+
+    #[test]
+    fn rugby_awards_try_and_losing_bonus() {
+        let rules = ScoringRules::rugby();
+        assert_eq!(
+            points(&rules, "Saracens 24, Leicester Tigers 19 (tries 4-1)"),
+            (5, 1)
+        );
+        assert_eq!(
+            points(&rules, "Saracens 10, Leicester Tigers 30 (tries 1-4)"),
+            (0, 5)
+        );
+        assert_eq!(
+            points(&rules, "Saracens 28, Leicester Tigers 22 (tries 4-4)"),
+            (5, 2)
+        );
+        // without tries only the losing bonus applies
+        assert_eq!(points(&rules, "Saracens 20, Leicester Tigers 15"), (4, 1));
+    }
+
+    #[test]
+    fn overtime_and_shootout_points() {
+        let rules = ScoringRules::iihf();
+        assert_eq!(points(&rules, "Sharks 3, Kings 2"), (3, 0));
+        assert_eq!(points(&rules, "Sharks 3, Kings 2 OT"), (2, 1));
+        assert_eq!(points(&rules, "Sharks 1, Kings 1 (2-3 pens)"), (1, 2));
+        let rules = ScoringRules::nhl();
+        assert_eq!(points(&rules, "Sharks 2, Kings 3 SO"), (1, 2));
+        assert_eq!(
+            points(&ScoringRules::football(), "Ants 1, Bees 1 (4-3 pens)"),
+            (3, 0)
+        );
+    }
+
+    #[test]
+    fn overtime_and_shootout_points_follow_win_and_loss() {
+        let rules: ScoringRules = "win=2, loss=1".parse().unwrap();
+        assert_eq!(points(&rules, "Ants 2, Bees 1"), (2, 1));
+        assert_eq!(points(&rules, "Ants 2, Bees 1 OT"), (2, 1));
+        assert_eq!(points(&rules, "Ants 1, Bees 1 (4-3 pens)"), (2, 1));
+        let rules: ScoringRules = "win=2, loss=1, so-loss=0".parse().unwrap();
+        assert_eq!(points(&rules, "Ants 1, Bees 2 SO"), (0, 2));
+        let rules: ScoringRules = "iihf, win=4".parse().unwrap();
+        assert_eq!(points(&rules, "Ants 3, Bees 2 OT"), (2, 1));
+    }
+
+    #[test]
+    fn forfeit_points_go_to_the_loser_of_awarded_games() {
+        let rules: ScoringRules = "forfeit=-1".parse().unwrap();
+        assert_eq!(points(&rules, "Ants 0, Bees 3 (awarded)"), (-1, 3));
+        assert_eq!(points(&rules, "Ants 0, Bees 3"), (0, 3));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{Game, MatchResult, TeamRecord};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A team's place in the rankings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankedEntry {
+    pub position: usize, // competition style, "1, 1, 3" for two teams level at the top
+    pub shared: bool,    // whether another team holds the same position
+    pub team: String,
+    pub record: TeamRecord,
+    pub notes: Vec<Note>,           // shown as footnotes by `Printer`
+    pub movement: Option<Movement>, // since the previous matchday, if there was one
+    pub form: Vec<MatchResult>, // the most recent results, oldest first, see `Standings::set_form_length`
+}
+
+/// Something the printed table points out about a team.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Note {
+    Awarded,                                  // has a result awarded rather than played
+    Annulled,                                 // had a game annulled
+    Expunged(String), // its results against this withdrawn team were expunged
+    Adjusted { points: i64, reason: String }, // see `Standings::adjust`
+}
+
+/// How a team's position changed since the previous matchday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Movement {
+    Up(usize),   // climbed by this many positions
+    Down(usize), // dropped by this many positions
+    Level,
+    New, // had no place in the previous table
+}
+
+impl Movement {
+    fn between(before: usize, after: usize) -> Movement {
+        match after.cmp(&before) {
+            Ordering::Less => Movement::Up(before - after),
+            Ordering::Greater => Movement::Down(after - before),
+            Ordering::Equal => Movement::Level,
+        }
+    }
+
+    /// The position before the move, given the one after it.
+    pub fn previous_position(self, position: usize) -> Option<usize> {
+        match self {
+            Movement::Up(n) => Some(position + n),
+            Movement::Down(n) => Some(position - n),
+            Movement::Level => Some(position),
+            Movement::New => None,
+        }
+    }
+}
+
+/// "▲2", "▼1", "=" or "new".
+impl fmt::Display for Movement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Movement::Up(n) => write!(f, "\u{25b2}{}", n),
+            Movement::Down(n) => write!(f, "\u{25bc}{}", n),
+            Movement::Level => f.write_str("="),
+            Movement::New => f.write_str("new"),
+        }
+    }
+}
+
+/// The complete rankings as of a matchday.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchdaySnapshot {
+    pub matchday: usize,
+    pub entries: Vec<RankedEntry>, // in ranking order
+    pub late_games: Vec<Game>,     // counted here, but played after the matchday was closed
+}
+
+impl MatchdaySnapshot {
+    /// Whether the snapshot was recomputed because of games played out of order.
+    pub fn is_revised(&self) -> bool {
+        !self.late_games.is_empty()
+    }
+
+    /// The entry of a single team, if it has played already.
+    pub fn entry(&self, team: &str) -> Option<&RankedEntry> {
+        self.entries.iter().find(|entry| entry.team == team)
+    }
+
+    /// The first `n` entries, extended by every team sharing the n-th position
+    /// if `include_ties` is set.
+    pub fn top(&self, n: usize, include_ties: bool) -> &[RankedEntry] {
+        let len = if include_ties {
+            self.entries
+                .iter()
+                .take_while(|entry| entry.position <= n)
+                .count()
+        } else {
+            n.min(self.entries.len())
+        };
+        &self.entries[..len]
+    }
+
+    // sets the movement of every entry relative to the `previous` matchday
+    pub(crate) fn compare_with(&mut self, previous: Option<&MatchdaySnapshot>) {
+        for entry in &mut self.entries {
+            entry.movement = previous.map(|previous| match previous.entry(&entry.team) {
+                Some(before) => Movement::between(before.position, entry.position),
+                None => Movement::New,
+            });
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use crate::{Fixture, Game, MatchResult};
+
+/// The kinds of streaks kept in `Stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreakKind {
+    Winning,     // consecutive wins
+    Unbeaten,    // consecutive games without a loss
+    CleanSheets, // consecutive games without conceding
+}
+
+impl StreakKind {
+    pub const ALL: [StreakKind; 3] = [
+        StreakKind::Winning,
+        StreakKind::Unbeaten,
+        StreakKind::CleanSheets,
+    ];
+
+    fn extends(self, fixture: &Fixture) -> bool {
+        match self {
+            StreakKind::Winning => fixture.result() == MatchResult::Win,
+            StreakKind::Unbeaten => fixture.result() != MatchResult::Loss,
+            StreakKind::CleanSheets => fixture.conceded() == 0,
+        }
+    }
+}
+
+/// Consecutive games of a team that share a property, e.g. a winning streak.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Streak {
+    pub kind: StreakKind,
+    pub team: String,
+    pub length: usize, // number of games
+    pub first: usize,  // matchday of the first game
+    pub last: usize,   // matchday of the last game
+    pub ongoing: bool, // the team has not played since
+}
+
+/// Streaks and outstanding games of one team or of the whole league, see
+/// `Standings::stats` and `Standings::team_stats`. Where several streaks or
+/// games share a record, all of them are listed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub team: Option<String>,       // `None` for the whole league
+    pub streaks: Vec<Streak>,       // the longest of each kind, in the order of `StreakKind::ALL`
+    pub biggest_wins: Vec<Game>,    // by goal margin
+    pub highest_scoring: Vec<Game>, // by goals scored by both teams
+}
+
+impl Stats {
+    /// The longest streaks of `kind`, empty if there was none.
+    pub fn longest(&self, kind: StreakKind) -> impl Iterator<Item = &Streak> {
+        self.streaks
+            .iter()
+            .filter(move |streak| streak.kind == kind)
+    }
+
+    // derives the records from the fixtures of every team involved, each
+    // list ordered by matchday
+    pub(crate) fn collect<'a, I>(team: Option<String>, fixtures: I) -> Stats
+    where
+        I: IntoIterator<Item = Vec<Fixture<'a>>>,
+    {
+        let mut streaks: Vec<Streak> = Vec::new();
+        let mut games: Vec<&Fixture> = Vec::new();
+        let fixtures: Vec<Vec<Fixture>> = fixtures.into_iter().collect();
+        for team_fixtures in &fixtures {
+            for &kind in &StreakKind::ALL {
+                streaks.extend(runs(kind, team_fixtures));
+            }
+            games.extend(team_fixtures);
+        }
+        // a game appears in the fixtures of both its teams
+        games.sort_by_key(|fixture| (fixture.matchday, fixture.position));
+        games.dedup_by_key(|fixture| fixture.position);
+
+        let mut longest = Vec::new();
+        for &kind in &StreakKind::ALL {
+            let max = streaks
+                .iter()
+                .filter(|streak| streak.kind == kind)
+                .map(|streak| streak.length)
+                .max();
+            longest.extend(
+                streaks
+                    .iter()
+                    .filter(|streak| streak.kind == kind && Some(streak.length) == max)
+                    .cloned(),
+            );
+        }
+        let wins: Vec<&Fixture> = games
+            .iter()
+            .copied()
+            .filter(|fixture| margin(fixture) > 0)
+            .collect();
+        Stats {
+            team,
+            streaks: longest,
+            biggest_wins: top_by(&wins, margin),
+            highest_scoring: top_by(&games, |fixture| {
+                u64::from(fixture.game.home_score) + u64::from(fixture.game.away_score)
+            }),
+        }
+    }
+}
+
+// every maximal streak of `kind` in `fixtures`
+fn runs(kind: StreakKind, fixtures: &[Fixture]) -> Vec<Streak> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for idx in 0..=fixtures.len() {
+        if idx < fixtures.len() && kind.extends(&fixtures[idx]) {
+            continue;
+        }
+        if idx > start {
+            runs.push(Streak {
+                kind,
+                team: fixtures[start].team.to_string(),
+                length: idx - start,
+                first: fixtures[start].matchday,
+                last: fixtures[idx - 1].matchday,
+                ongoing: idx == fixtures.len(),
+            });
+        }
+        start = idx + 1;
+    }
+    runs
+}
+
+// goals the winner of `fixture` won by, 0 for draws and games decided on penalties
+fn margin(fixture: &Fixture) -> u64 {
+    let (home, away) = (fixture.game.home_score, fixture.game.away_score);
+    u64::from(home.max(away) - home.min(away))
+}
+
+// the games sharing the highest `key`, e.g. the biggest margin
+fn top_by<F: Fn(&Fixture) -> u64>(fixtures: &[&Fixture], key: F) -> Vec<Game> {
+    let max = match fixtures.iter().map(|fixture| key(fixture)).max() {
+        Some(max) => max,
+        None => return Vec::new(),
+    };
+    fixtures
+        .iter()
+        .filter(|fixture| key(fixture) == max)
+        .map(|fixture| fixture.game.clone().into_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_standings;
+    use crate::Standings;
+
+    #[test]
+    fn team_stats_find_longest_streaks() {
+        let stats = sample_standings().team_stats("Aptos FC").unwrap();
+        let winning: Vec<&Streak> = stats.longest(StreakKind::Winning).collect();
+        assert_eq!(winning.len(), 1);
+        assert_eq!(
+            (winning[0].length, winning[0].first, winning[0].last),
+            (3, 2, 4)
+        );
+        assert!(winning[0].ongoing);
+        assert_eq!(
+            stats
+                .longest(StreakKind::CleanSheets)
+                .next()
+                .unwrap()
+                .length,
+            1
+        );
+        assert_eq!(
+            stats.biggest_wins,
+            vec![Game::parse_line("Aptos FC 2, Monterey United 0", 1).unwrap()]
+        );
+        assert!(sample_standings()
+            .team_stats("Watsonville Wolves")
+            .is_none());
+    }
+
+    #[test]
+    fn league_stats_list_shared_records() {
+        let stats = sample_standings().stats();
+        assert_eq!(stats.team, None);
+        let clean_sheets: Vec<&Streak> = stats.longest(StreakKind::CleanSheets).collect();
+        assert_eq!(clean_sheets.len(), 1);
+        assert_eq!(clean_sheets[0].team, "Capitola Seahorses");
+        assert_eq!(
+            (clean_sheets[0].length, clean_sheets[0].ongoing),
+            (2, false)
+        );
+        assert_eq!(
+            stats.biggest_wins,
+            vec![Game::parse_line("San Jose Earthquakes 1, Felton Lumberjacks 4", 1).unwrap()]
+        );
+        assert_eq!(
+            stats.highest_scoring,
+            vec![Game::parse_line("Capitola Seahorses 5, San Jose Earthquakes 5", 1).unwrap()]
+        );
+
+        let mut standings = Standings::default();
+        for line in &[
+            "Aptos FC 1, Felton Lumberjacks 0",
+            "Capitola Seahorses 2, Santa Cruz Slugs 1",
+        ] {
+            standings.ingest(line.parse().unwrap()).unwrap();
+        }
+        let stats = standings.stats();
+        let winning: Vec<&str> = stats
+            .longest(StreakKind::Winning)
+            .map(|streak| streak.team.as_str())
+            .collect();
+        assert_eq!(winning, vec!["Aptos FC", "Capitola Seahorses"]);
+        assert_eq!(stats.biggest_wins.len(), 2);
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+/// A compact handle for a team name interned in `Teams`.
+///
+/// Ids are only meaningful together with the `Teams` registry that handed
+/// them out, e.g. the one of a `Standings`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TeamId(u32);
+
+/// Registry interning team names into `TeamId`s, so each name is stored once
+/// no matter how many games a team plays.
+#[derive(Debug, Default, Clone)]
+pub struct Teams {
+    names: Vec<Rc<str>>,           // indexed by id
+    ids: HashMap<Rc<str>, TeamId>, // reverse lookup, sharing the names above
+}
+
+impl Teams {
+    /// The id of `name`, registering it first if needed. Only allocates for
+    /// names that have not been seen before.
+    ///
+    /// Panics if `u32::MAX` teams are registered already.
+    pub fn intern(&mut self, name: &str) -> TeamId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = TeamId(u32::try_from(self.names.len()).expect("too many teams"));
+        let name: Rc<str> = name.into();
+        self.names.push(Rc::clone(&name));
+        self.ids.insert(name, id);
+        id
+    }
+
+    /// The id of `name`, if it has been registered.
+    pub fn id(&self, name: &str) -> Option<TeamId> {
+        self.ids.get(name).copied()
+    }
+
+    /// The name behind `id`.
+    ///
+    /// Panics if `id` was handed out by another registry with more teams.
+    pub fn name(&self, id: TeamId) -> &str {
+        &self.names[id.0 as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// All registered teams in the order they were first seen.
+    pub fn iter(&self) -> impl Iterator<Item = (TeamId, &str)> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| (TeamId(idx as u32), &**name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intern_hands_out_one_id_per_name() {
+        let mut teams = Teams::default();
+        let aptos = teams.intern("Aptos FC");
+        let felton = teams.intern("Felton Lumberjacks");
+        assert_eq!(teams.intern("Aptos FC"), aptos);
+        assert_ne!(aptos, felton);
+        assert_eq!(teams.len(), 2);
+        assert_eq!(teams.name(felton), "Felton Lumberjacks");
+        assert_eq!(teams.id("Aptos FC"), Some(aptos));
+        assert_eq!(teams.id("Monterey United"), None);
+        // the lookup shares the stored names
+        assert!(teams
+            .ids
+            .keys()
+            .all(|key| teams.names.iter().any(|name| Rc::ptr_eq(name, key))));
+        assert_eq!(
+            teams.iter().map(|(_, name)| name).collect::<Vec<_>>(),
+            vec!["Aptos FC", "Felton Lumberjacks"]
+        );
+    }
+}
@@ -0,0 +1,431 @@
+use crate::{ParseTiebreakerError, Standings, TeamId, TeamRecord};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// A criterion that separates teams level on points.
+///
+/// Tiebreakers are applied in the order they are configured on `Standings`.
+/// Teams that are still level after the last one are ordered alphabetically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tiebreaker {
+    GoalDifference,
+    GoalsScored,
+    AwayGoals,
+    Wins,
+    HeadToHeadPoints, // points from the games among the tied teams only
+    HeadToHeadGoalDifference,
+    HeadToHeadGoalsScored,
+    HeadToHeadAwayGoals,
+    FairPlay,           // fewest disciplinary points
+    DrawingOfLots(u64), // seeded, so a drawing can be reproduced
+}
+
+impl Tiebreaker {
+    /// UEFA club competition group stage, to be used with
+    /// `Standings::set_head_to_head_reapplied(true)`.
+    pub const UEFA: &'static [Tiebreaker] = &[
+        Tiebreaker::HeadToHeadPoints,
+        Tiebreaker::HeadToHeadGoalDifference,
+        Tiebreaker::HeadToHeadGoalsScored,
+        Tiebreaker::HeadToHeadAwayGoals,
+        Tiebreaker::GoalDifference,
+        Tiebreaker::GoalsScored,
+        Tiebreaker::AwayGoals,
+        Tiebreaker::Wins,
+        Tiebreaker::FairPlay,
+    ];
+
+    /// Whether the criterion only looks at the games among the tied teams.
+    pub fn is_head_to_head(self) -> bool {
+        matches!(
+            self,
+            Tiebreaker::HeadToHeadPoints
+                | Tiebreaker::HeadToHeadGoalDifference
+                | Tiebreaker::HeadToHeadGoalsScored
+                | Tiebreaker::HeadToHeadAwayGoals
+        )
+    }
+}
+
+impl FromStr for Tiebreaker {
+    type Err = ParseTiebreakerError;
+
+    fn from_str(s: &str) -> Result<Tiebreaker, ParseTiebreakerError> {
+        let err = || ParseTiebreakerError(s.to_string());
+        Ok(match s {
+            "gd" | "goal-difference" => Tiebreaker::GoalDifference,
+            "gf" | "goals-scored" => Tiebreaker::GoalsScored,
+            "away-goals" => Tiebreaker::AwayGoals,
+            "wins" => Tiebreaker::Wins,
+            "h2h-points" => Tiebreaker::HeadToHeadPoints,
+            "h2h-gd" | "h2h-goal-difference" => Tiebreaker::HeadToHeadGoalDifference,
+            "h2h-gf" | "h2h-goals-scored" => Tiebreaker::HeadToHeadGoalsScored,
+            "h2h-away-goals" => Tiebreaker::HeadToHeadAwayGoals,
+            "fair-play" => Tiebreaker::FairPlay,
+            "lots" => Tiebreaker::DrawingOfLots(0),
+            _ if s.starts_with("lots:") => {
+                Tiebreaker::DrawingOfLots(s["lots:".len()..].parse().map_err(|_| err())?)
+            }
+            _ => return Err(err()),
+        })
+    }
+}
+
+impl fmt::Display for Tiebreaker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Tiebreaker::GoalDifference => write!(f, "goal-difference"),
+            Tiebreaker::GoalsScored => write!(f, "goals-scored"),
+            Tiebreaker::AwayGoals => write!(f, "away-goals"),
+            Tiebreaker::Wins => write!(f, "wins"),
+            Tiebreaker::HeadToHeadPoints => write!(f, "h2h-points"),
+            Tiebreaker::HeadToHeadGoalDifference => write!(f, "h2h-goal-difference"),
+            Tiebreaker::HeadToHeadGoalsScored => write!(f, "h2h-goals-scored"),
+            Tiebreaker::HeadToHeadAwayGoals => write!(f, "h2h-away-goals"),
+            Tiebreaker::FairPlay => write!(f, "fair-play"),
+            Tiebreaker::DrawingOfLots(seed) => write!(f, "lots:{}", seed),
+        }
+    }
+}
+
+/// Orders all teams of `standings` by points and the configured tiebreakers,
+/// together with their competition style position ("1224"): teams that no
+/// criterion could separate share a position, the alphabetical order among
+/// them is only for presentation.
+pub(crate) fn rank(standings: &Standings) -> Vec<(usize, TeamId)> {
+    let mut teams: Vec<TeamId> = standings.records.keys().copied().collect();
+    let mut shared = HashSet::new();
+    sort_by_key_desc(&mut teams, |team| standings.records[&team].points);
+    for group in tied_groups(&mut teams, |team| standings.records[&team].points) {
+        resolve(standings, group, &standings.tiebreakers, &mut shared);
+    }
+    let mut position = 0;
+    teams
+        .iter()
+        .enumerate()
+        .map(|(idx, &team)| {
+            if !shared.contains(&team) {
+                position = idx + 1;
+            }
+            (position, team)
+        })
+        .collect()
+}
+
+// Orders a group of teams that are level so far by the remaining criteria.
+// Teams sharing the position of the team before them are added to `shared`.
+fn resolve(
+    standings: &Standings,
+    group: &mut [TeamId],
+    criteria: &[Tiebreaker],
+    shared: &mut HashSet<TeamId>,
+) {
+    let head_to_head = criteria.iter().take_while(|c| c.is_head_to_head()).count();
+    if head_to_head > 0 {
+        let (head_to_head, rest) = criteria.split_at(head_to_head);
+        resolve_mini_league(standings, group, head_to_head, rest, shared);
+        return;
+    }
+    let (criterion, rest) = match criteria.split_first() {
+        Some(split) => split,
+        None => {
+            group.sort_by_key(|&team| standings.teams.name(team));
+            shared.extend(&group[1..]);
+            return;
+        }
+    };
+    let keys: HashMap<TeamId, i64> = group
+        .iter()
+        .map(|&team| {
+            (
+                team,
+                key(standings, &standings.records[&team], team, *criterion),
+            )
+        })
+        .collect();
+    sort_by_key_desc(group, |team| keys[&team]);
+    for subgroup in tied_groups(group, |team| keys[&team]) {
+        resolve(standings, subgroup, rest, shared);
+    }
+}
+
+// Applies consecutive `head_to_head` criteria on the games among `group`. Teams
+// still level are passed on to the `rest` of the criteria, or, if head-to-head
+// is reapplied and the group got smaller, start over with a mini-league of their own.
+fn resolve_mini_league(
+    standings: &Standings,
+    group: &mut [TeamId],
+    head_to_head: &[Tiebreaker],
+    rest: &[Tiebreaker],
+    shared: &mut HashSet<TeamId>,
+) {
+    let mini = mini_table(standings, group);
+    let keys: HashMap<TeamId, Vec<i64>> = group
+        .iter()
+        .map(|&team| {
+            let key = head_to_head
+                .iter()
+                .map(|criterion| key(standings, &mini[&team], team, *criterion))
+                .collect();
+            (team, key)
+        })
+        .collect();
+    sort_by_key_desc(group, |team| keys[&team].clone());
+    let size = group.len();
+    for subgroup in tied_groups(group, |team| keys[&team].clone()) {
+        if standings.reapply_head_to_head && subgroup.len() < size {
+            resolve_mini_league(standings, subgroup, head_to_head, rest, shared);
+        } else {
+            resolve(standings, subgroup, rest, shared);
+        }
+    }
+}
+
+// Value of `criterion` for `team`, higher is better. `record` is the team's
+// overall record, or its mini-league record for head-to-head criteria.
+fn key(standings: &Standings, record: &TeamRecord, team: TeamId, criterion: Tiebreaker) -> i64 {
+    match criterion {
+        Tiebreaker::GoalDifference | Tiebreaker::HeadToHeadGoalDifference => {
+            record.goal_difference()
+        }
+        Tiebreaker::GoalsScored | Tiebreaker::HeadToHeadGoalsScored => saturate(record.goals_for),
+        Tiebreaker::AwayGoals | Tiebreaker::HeadToHeadAwayGoals => saturate(record.away_goals),
+        Tiebreaker::Wins => i64::from(record.won),
+        Tiebreaker::HeadToHeadPoints => record.points,
+        Tiebreaker::FairPlay => -i64::from(standings.fair_play.get(&team).copied().unwrap_or(0)),
+        Tiebreaker::DrawingOfLots(seed) => lot(seed, standings.teams.name(team)),
+    }
+}
+
+// Records built from the games among `group` only.
+fn mini_table(standings: &Standings, group: &[TeamId]) -> HashMap<TeamId, TeamRecord> {
+    let mut table: HashMap<TeamId, TeamRecord> = group
+        .iter()
+        .map(|&team| (team, TeamRecord::default()))
+        .collect();
+    for game in standings.games() {
+        if !(table.contains_key(&game.home) && table.contains_key(&game.away)) {
+            continue;
+        }
+        let (home_points, away_points) = standings.points(game);
+        // the totals may leave the range the full table is checked for, e.g.
+        // with negative forfeit points, but only serve as sort keys
+        let home = table[&game.home].with_game_saturating(
+            game.home_score,
+            game.away_score,
+            game.home_result(),
+            home_points,
+            false,
+        );
+        let away = table[&game.away].with_game_saturating(
+            game.away_score,
+            game.home_score,
+            game.home_result().reverse(),
+            away_points,
+            true,
+        );
+        table.insert(game.home, home);
+        table.insert(game.away, away);
+    }
+    table
+}
+
+fn saturate(n: u64) -> i64 {
+    i64::try_from(n).unwrap_or(i64::MAX)
+}
+
+// Deterministic pseudo-random number for a team (FNV-1a over seed and name),
+// stable across platforms and compiler versions unlike std's hasher.
+fn lot(seed: u64, team: &str) -> i64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in seed.to_le_bytes().iter().chain(team.as_bytes()) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    (hash >> 1) as i64
+}
+
+fn sort_by_key_desc<K: Ord, F: Fn(TeamId) -> K>(teams: &mut [TeamId], key: F) {
+    teams.sort_by_key(|&team| Reverse(key(team)));
+}
+
+// Splits sorted `teams` into runs of equal key that contain more than one team.
+fn tied_groups<K: PartialEq, F: Fn(TeamId) -> K>(
+    teams: &mut [TeamId],
+    key: F,
+) -> Vec<&mut [TeamId]> {
+    let mut groups = Vec::new();
+    let mut rest = teams;
+    while !rest.is_empty() {
+        let first = key(rest[0]);
+        let len = rest.iter().take_while(|&&team| key(team) == first).count();
+        let (group, tail) = rest.split_at_mut(len);
+        if group.len() > 1 {
+            groups.push(group);
+        }
+        rest = tail;
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Game;
+
+    fn standings(tiebreakers: &[Tiebreaker], games: &[&str]) -> Standings {
+        let mut standings = Standings::default();
+        standings.set_tiebreakers(tiebreakers.to_vec());
+        for game in games {
+            standings.ingest(game.parse::<Game>().unwrap()).unwrap();
+        }
+        standings
+    }
+
+    fn order(standings: &Standings) -> Vec<String> {
+        standings
+            .rankings()
+            .into_iter()
+            .map(|entry| entry.team)
+            .collect()
+    }
+
+    #[test]
+    fn tiebreakers_parse_from_str() {
+        let parsed: Vec<Tiebreaker> = "gd,gf,away-goals,wins,h2h-points,h2h-gd,fair-play,lots:7"
+            .split(',')
+            .map(|s| s.parse().unwrap())
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![
+                Tiebreaker::GoalDifference,
+                Tiebreaker::GoalsScored,
+                Tiebreaker::AwayGoals,
+                Tiebreaker::Wins,
+                Tiebreaker::HeadToHeadPoints,
+                Tiebreaker::HeadToHeadGoalDifference,
+                Tiebreaker::FairPlay,
+                Tiebreaker::DrawingOfLots(7),
+            ]
+        );
+        assert_eq!(
+            "coin".parse::<Tiebreaker>(),
+            Err(ParseTiebreakerError("coin".to_string()))
+        );
+    }
+
+    #[test]
+    fn alphabetical_is_last_resort() {
+        let games = ["Zebras 1, Ants 0", "Ants 2, Zebras 1"];
+        assert_eq!(order(&standings(&[], &games)), vec!["Ants", "Zebras"]);
+        assert_eq!(
+            order(&standings(&[Tiebreaker::GoalsScored], &games)),
+            vec!["Ants", "Zebras"]
+        );
+    }
+
+    #[test]
+    fn goal_difference_then_goals_scored() {
+        let games = [
+            "Aptos FC 3, Monterey United 0",
+            "Zebras 2, Ants 0",
+            "Monterey United 2, Aptos FC 0",
+            "Ants 1, Zebras 0",
+        ];
+        // all on 3 points, Aptos FC and Zebras on +1, Aptos FC scored more
+        let ranked = standings(
+            &[Tiebreaker::GoalDifference, Tiebreaker::GoalsScored],
+            &games,
+        );
+        assert_eq!(
+            order(&ranked),
+            vec!["Aptos FC", "Zebras", "Monterey United", "Ants"]
+        );
+    }
+
+    #[test]
+    fn head_to_head_uses_games_among_tied_teams() {
+        let games = [
+            "Bees 1, Zebras 0",
+            "Zebras 5, Ants 0",
+            "Ants 0, Wasps 0",
+            "Wasps 1, Bees 0",
+        ];
+        // Bees and Zebras on 3 points, Zebras have the better goal difference
+        let ranked = standings(&[Tiebreaker::GoalDifference], &games);
+        assert_eq!(order(&ranked), vec!["Wasps", "Zebras", "Bees", "Ants"]);
+        let ranked = standings(
+            &[Tiebreaker::HeadToHeadPoints, Tiebreaker::GoalDifference],
+            &games,
+        );
+        assert_eq!(order(&ranked), vec!["Wasps", "Bees", "Zebras", "Ants"]);
+    }
+
+    #[test]
+    fn fair_play_and_lots() {
+        let games = ["Ants 1, Zebras 1"];
+        let mut ranked = standings(&[Tiebreaker::FairPlay], &games);
+        ranked.add_fair_play_points("Ants", 4);
+        assert_eq!(order(&ranked), vec!["Zebras", "Ants"]);
+
+        let first = order(&standings(&[Tiebreaker::DrawingOfLots(1)], &games))[0].to_string();
+        let again = order(&standings(&[Tiebreaker::DrawingOfLots(1)], &games))[0].to_string();
+        assert_eq!(first, again);
+    }
+
+    #[test]
+    fn head_to_head_reapplied_to_teams_still_level() {
+        let games = [
+            "Ajax 2, Benfica 0",
+            "Benfica 1, Ajax 0",
+            "Ajax 0, Celtic 2",
+            "Celtic 2, Ajax 0",
+            "Benfica 1, Celtic 2",
+            "Celtic 1, Benfica 0",
+            "Ajax 1, Dynamo 0",
+            "Dynamo 0, Ajax 1",
+            "Ajax 1, Dynamo 0",
+            "Benfica 5, Dynamo 0",
+            "Dynamo 0, Benfica 5",
+            "Benfica 5, Dynamo 0",
+        ];
+        // Ajax, Benfica and Celtic on 12 points. Celtic wins the mini-league,
+        // Ajax and Benfica are level in it, but Ajax won their own games 2-1.
+        let mut ranked = standings(Tiebreaker::UEFA, &games);
+        assert_eq!(order(&ranked), vec!["Celtic", "Benfica", "Ajax", "Dynamo"]);
+        ranked.set_head_to_head_reapplied(true);
+        assert_eq!(order(&ranked), vec!["Celtic", "Ajax", "Benfica", "Dynamo"]);
+    }
+
+    #[test]
+    fn head_to_head_totals_saturate() {
+        let mut standings = Standings::default();
+        standings.set_scoring(
+            "win=9223372036854775807, forfeit=-9223372036854775807"
+                .parse()
+                .unwrap(),
+        );
+        standings.set_tiebreakers(vec![Tiebreaker::HeadToHeadPoints]);
+        let games = [
+            "A 0, X1 3 (awarded)",
+            "B 0, X2 3 (awarded)",
+            "A 1, B 0",
+            "A 1, B 0",
+            "B 1, C 0",
+            "B 1, D 0",
+        ];
+        for game in &games {
+            standings.ingest(game.parse::<Game>().unwrap()).unwrap();
+        }
+        standings.adjust("X1", -1, "").unwrap();
+        standings.adjust("X2", -1, "").unwrap();
+        // A beat B twice, which is beyond the range of the mini-table's points
+        let ranked = order(&standings);
+        let position = |team: &str| ranked.iter().position(|t| t == team).unwrap();
+        assert!(position("A") < position("B"));
+    }
+}